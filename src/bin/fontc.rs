@@ -0,0 +1,104 @@
+//! fontc：从 BDF / HZK 字库生成只包含所需字符的字体模块
+//!
+//! 用法:
+//!   fontc (--bdf FILE | --hzk FILE [--hzk-size 16]) [--size 16x16] [--name CJK16]
+//!         [--chars TEXT] [--chars-file FILE] [--scan FILE_OR_DIR]...
+//!         [--base FONT16X16] [--crate-path oled] [--proportional] [-o OUT.rs]
+
+use oled::font::compiler::{self, BdfFont, GlyphSource, HzkFont, Options};
+use std::path::Path;
+use std::{env, fs, process};
+
+fn usage() -> ! {
+    eprintln!(
+        "usage: fontc (--bdf FILE | --hzk FILE [--hzk-size N]) [--size HxW] [--name NAME]\n\
+         \x20            [--chars TEXT] [--chars-file FILE] [--scan PATH]...\n\
+         \x20            [--base FONT16X16] [--crate-path oled] [--proportional] [-o OUT]"
+    );
+    process::exit(2);
+}
+
+// 递归扫描目录中的 .rs 文件，收集其中的字符串字面量
+fn scan(path: &Path, texts: &mut Vec<String>) -> std::io::Result<()> {
+    if path.is_dir() {
+        for entry in fs::read_dir(path)? {
+            scan(&entry?.path(), texts)?;
+        }
+    } else if path.extension().is_some_and(|e| e == "rs") {
+        texts.extend(compiler::string_literals(&fs::read_to_string(path)?));
+    }
+    Ok(())
+}
+
+fn run() -> Result<(), Box<dyn std::error::Error>> {
+    let mut args = env::args().skip(1);
+    let mut bdf = None;
+    let mut hzk = None;
+    let mut hzk_size = 16;
+    let mut size = None;
+    let mut texts: Vec<String> = Vec::new();
+    let mut output = None;
+    let mut opts = Options {
+        name: "CUSTOM".to_string(),
+        w: 16,
+        h: 16,
+        base: None,
+        crate_path: "oled".to_string(),
+        proportional: false,
+    };
+
+    while let Some(arg) = args.next() {
+        let mut value = || args.next().unwrap_or_else(|| usage());
+        match arg.as_str() {
+            "--bdf" => bdf = Some(value()),
+            "--hzk" => hzk = Some(value()),
+            "--hzk-size" => hzk_size = value().parse()?,
+            "--size" => size = Some(value()),
+            "--name" => opts.name = value(),
+            "--chars" => texts.push(value()),
+            "--chars-file" => texts.push(fs::read_to_string(value())?),
+            "--scan" => scan(Path::new(&value()), &mut texts)?,
+            "--base" => opts.base = Some(value()),
+            "--crate-path" => opts.crate_path = value(),
+            "--proportional" => opts.proportional = true,
+            "-o" | "--output" => output = Some(value()),
+            _ => usage(),
+        }
+    }
+
+    // 默认字符格与字库点阵大小一致，高度向上取整到8的倍数
+    match size {
+        Some(s) => {
+            let (h, w) = s.split_once('x').unwrap_or_else(|| usage());
+            opts.h = h.parse()?;
+            opts.w = w.parse()?;
+        }
+        None if hzk.is_some() => {
+            opts.w = hzk_size;
+            opts.h = hzk_size.div_ceil(8) * 8;
+        }
+        None => {}
+    }
+
+    let source: Box<dyn GlyphSource> = match (bdf, hzk) {
+        (Some(path), None) => Box::new(BdfFont::parse(&fs::read_to_string(path)?)?),
+        (None, Some(path)) => Box::new(HzkFont::new(fs::read(path)?, hzk_size)),
+        _ => usage(),
+    };
+
+    let chars = compiler::collect_chars(texts.iter().map(String::as_str));
+    let code = compiler::compile(source.as_ref(), &chars, &opts)?;
+    match output {
+        Some(path) => fs::write(path, code)?,
+        None => print!("{}", code),
+    }
+    eprintln!("fontc: {} glyphs", chars.len());
+    Ok(())
+}
+
+fn main() {
+    if let Err(e) = run() {
+        eprintln!("fontc: {}", e);
+        process::exit(1);
+    }
+}
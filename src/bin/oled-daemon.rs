@@ -0,0 +1,173 @@
+//! oled-daemon：独占屏幕，在 Unix 套接字上接受多个进程的绘制请求
+//!
+//! 用法:
+//!   oled-daemon [--device /dev/i2c-1] [--address 0x3c] [--controller ssd1306|sh1106]
+//!               [--socket /run/oled.sock] [--fps 10] [--region 名称=X,Y,W,H]...
+//!               [--http 127.0.0.1:8080] [--mqtt localhost:1883] [--mqtt-prefix oled]
+//!               [--simulate]
+//!
+//! 协议见 `oled::daemon`，例如：
+//!   echo "text screen align=center valign=middle Hello" | socat - UNIX-CONNECT:/run/oled.sock
+//!
+//! `--http` 同时提供 HTTP/JSON 接口（需要 `http` 特性，见 `oled::http`）；
+//! `--mqtt` 连接 MQTT 代理，订阅主题来更新屏幕并发布状态（需要 `mqtt` 特性，见 `oled::mqtt`），
+//! IPv6 地址带端口时写成 `[::1]:1883`；
+//! `--simulate` 不打开I2C设备，用模拟屏幕在开发机上测试。
+
+use oled::daemon::Server;
+use oled::{Controller, Display, Rect};
+use std::os::unix::net::UnixListener;
+use std::sync::Arc;
+use std::time::Duration;
+use std::{env, fs, io, process};
+
+fn usage() -> ! {
+    eprintln!(
+        "usage: oled-daemon [--device DEV] [--address ADDR] [--controller ssd1306|sh1106]\n\
+         \x20                  [--socket PATH] [--fps N] [--region NAME=X,Y,W,H]...\n\
+         \x20                  [--http ADDR] [--mqtt HOST[:PORT]|[IPV6]:PORT] [--mqtt-prefix PREFIX]\n\
+         \x20                  [--simulate]"
+    );
+    process::exit(2);
+}
+
+// 支持十进制和 0x 开头的十六进制
+fn parse_u8(s: &str) -> Result<u8, std::num::ParseIntError> {
+    match s.strip_prefix("0x").or_else(|| s.strip_prefix("0X")) {
+        Some(hex) => u8::from_str_radix(hex, 16),
+        None => s.parse(),
+    }
+}
+
+fn parse_region(s: &str) -> Option<(String, Rect)> {
+    let (name, rect) = s.split_once('=')?;
+    let v: Vec<u8> = rect
+        .split(',')
+        .map(|n| n.parse().ok())
+        .collect::<Option<_>>()?;
+    match v[..] {
+        [x, y, w, h] => Some((name.to_string(), Rect::new(x, y, w, h))),
+        _ => None,
+    }
+}
+
+// `HOST`、`HOST:PORT`、`[IPv6]:PORT`，或不带括号和端口的 IPv6 地址
+#[cfg(feature = "mqtt")]
+fn parse_broker(s: &str) -> Option<(String, u16)> {
+    const PORT: u16 = 1883;
+    if let Some(rest) = s.strip_prefix('[') {
+        let (host, rest) = rest.split_once(']')?;
+        let port = match rest {
+            "" => PORT,
+            _ => rest.strip_prefix(':')?.parse().ok()?,
+        };
+        return Some((host.to_string(), port));
+    }
+    match s.split_once(':') {
+        Some((host, port)) if !port.contains(':') => Some((host.to_string(), port.parse().ok()?)),
+        _ => Some((s.to_string(), PORT)),
+    }
+}
+
+fn run() -> Result<(), Box<dyn std::error::Error>> {
+    let mut args = env::args().skip(1);
+    let mut device = "/dev/i2c-1".to_string();
+    let mut socket = "/run/oled.sock".to_string();
+    let mut fps: u32 = 10;
+    let mut regions = Vec::new();
+    let mut http: Option<String> = None;
+    let mut mqtt: Option<String> = None;
+    let mut prefix = "oled".to_string();
+    let mut simulate = false;
+    while let Some(arg) = args.next() {
+        let mut value = || args.next().unwrap_or_else(|| usage());
+        match arg.as_str() {
+            "--device" => device = value(),
+            "--address" => oled::set_address(parse_u8(&value())?),
+            "--controller" => oled::set_controller(match value().as_str() {
+                "ssd1306" => Controller::Ssd1306,
+                "sh1106" => Controller::Sh1106,
+                _ => usage(),
+            }),
+            "--socket" => socket = value(),
+            "--fps" => fps = value().parse()?,
+            "--region" => regions.push(parse_region(&value()).unwrap_or_else(|| usage())),
+            "--http" => http = Some(value()),
+            "--mqtt" => mqtt = Some(value()),
+            "--mqtt-prefix" => prefix = value(),
+            "--simulate" => simulate = true,
+            _ => usage(),
+        }
+    }
+
+    #[cfg(not(feature = "http"))]
+    if http.is_some() {
+        return Err("built without the http feature".into());
+    }
+    #[cfg(not(feature = "mqtt"))]
+    if mqtt.is_some() || prefix != "oled" {
+        return Err("built without the mqtt feature".into());
+    }
+
+    let display = if simulate {
+        Display::simulated()
+    } else {
+        Display::open(&device)?
+    };
+    let mut server = Server::new(display).refresh_interval(Duration::from_secs(1) / fps.max(1));
+    for (name, rect) in &regions {
+        server = server.region(name, *rect);
+    }
+    // 上次运行留下的套接字文件
+    match fs::remove_file(&socket) {
+        Err(e) if e.kind() != io::ErrorKind::NotFound => return Err(e.into()),
+        _ => {}
+    }
+    let listener = UnixListener::bind(&socket)?;
+    // 几个接口共用同一个守护进程
+    let server = Arc::new(server);
+    #[cfg(feature = "http")]
+    if let Some(addr) = http {
+        let server = server.clone();
+        std::thread::spawn(move || {
+            if let Err(e) = oled::http::serve(&server, &addr) {
+                eprintln!("oled-daemon: http: {}", e);
+                process::exit(1);
+            }
+        });
+    }
+    #[cfg(feature = "mqtt")]
+    if let Some(broker) = mqtt {
+        let (host, port) = parse_broker(&broker).ok_or_else(|| format!("bad --mqtt {}", broker))?;
+        let bridge = oled::mqtt::MqttBridge::new(&host, port).prefix(&prefix);
+        let server = server.clone();
+        std::thread::spawn(move || bridge.run(&server));
+    }
+    server.serve(listener)?;
+    Ok(())
+}
+
+fn main() {
+    if let Err(e) = run() {
+        eprintln!("oled-daemon: {}", e);
+        process::exit(1);
+    }
+}
+
+#[cfg(all(test, feature = "mqtt"))]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn broker_addresses() {
+        let broker = |host: &str, port| Some((host.to_string(), port));
+        assert_eq!(parse_broker("localhost"), broker("localhost", 1883));
+        assert_eq!(parse_broker("localhost:1884"), broker("localhost", 1884));
+        assert_eq!(parse_broker("[::1]:1884"), broker("::1", 1884));
+        assert_eq!(parse_broker("[fe80::1]"), broker("fe80::1", 1883));
+        assert_eq!(parse_broker("fe80::1"), broker("fe80::1", 1883));
+        assert_eq!(parse_broker("localhost:mqtt"), None);
+        assert_eq!(parse_broker("[::1]1884"), None);
+        assert_eq!(parse_broker("[::1"), None);
+    }
+}
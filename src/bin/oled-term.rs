@@ -0,0 +1,154 @@
+//! oled-term：在伪终端中运行程序，把输出显示在屏幕上
+//!
+//! 用法:
+//!   oled-term [--device /dev/i2c-1] [--font 8x8] [--fps 10] -- 命令 [参数...]
+//!
+//! 终端大小按字体计算；收到 SIGUSR1 时切换到下一个内置等宽字体，并通知程序新的终端大小。
+
+use oled::font::{FontFace, FontRegistry, SharedFont};
+use oled::pty::{Pty, terminal_size};
+use oled::{AutoFlush, Buffer, Console, Display};
+use std::io::{Read, Write};
+use std::process::{self, Command};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::mpsc::{self, RecvTimeoutError};
+use std::time::{Duration, Instant};
+use std::{env, io, thread};
+
+fn usage() -> ! {
+    eprintln!("usage: oled-term [--device DEV] [--font NAME] [--fps N] -- COMMAND [ARGS]...");
+    process::exit(2);
+}
+
+static SWITCH_FONT: AtomicBool = AtomicBool::new(false);
+
+extern "C" fn on_sigusr1(_: libc::c_int) {
+    SWITCH_FONT.store(true, Ordering::Relaxed);
+}
+
+// 终端按列寻址，比例字体会让各列错位
+fn is_monospace(font: &dyn FontFace) -> bool {
+    font.get_char_width('i') == font.get_char_width('M')
+}
+
+fn run() -> Result<i32, Box<dyn std::error::Error>> {
+    let mut args = env::args().skip(1);
+    let mut device = "/dev/i2c-1".to_string();
+    let mut font_name = "8x8".to_string();
+    let mut fps: u32 = 10;
+    let mut command = Vec::new();
+    while let Some(arg) = args.next() {
+        let mut value = || args.next().unwrap_or_else(|| usage());
+        match arg.as_str() {
+            "--device" => device = value(),
+            "--font" => font_name = value(),
+            "--fps" => fps = value().parse()?,
+            "--" => {
+                command.extend(args.by_ref());
+                break;
+            }
+            _ => usage(),
+        }
+    }
+    if command.is_empty() {
+        usage();
+    }
+
+    let fonts = FontRegistry::with_builtin();
+    let names: Vec<String> = fonts
+        .names()
+        .filter(|name| fonts.get(name).is_some_and(|f| is_monospace(f.as_ref())))
+        .map(String::from)
+        .collect();
+    let font: SharedFont = fonts
+        .get(&font_name)
+        .ok_or_else(|| format!("unknown font {}", font_name))?;
+    if !is_monospace(font.as_ref()) {
+        return Err(format!("font {} is not monospace", font_name).into());
+    }
+    let (rows, cols) = terminal_size(font.as_ref());
+
+    let mut display = Display::open(&device)?;
+    display.init();
+    display.clear();
+    let buffer = Buffer::terminal(rows as usize).columns(cols as usize);
+    let mut console = Console::with_buffer(display, buffer, font).auto_flush(AutoFlush::Never);
+
+    let mut cmd = Command::new(&command[0]);
+    cmd.args(&command[1..]);
+    let mut pty = Pty::spawn(cmd, rows, cols)?;
+    unsafe {
+        libc::signal(libc::SIGUSR1, on_sigusr1 as *const () as libc::sighandler_t);
+    }
+
+    // 程序输出在单独的线程中读取，子进程退出后读取出错，通道随之关闭
+    let (tx, rx) = mpsc::channel();
+    let mut output = pty.try_clone_master()?;
+    thread::spawn(move || {
+        let mut buf = [0u8; 4096];
+        while let Ok(n) = output.read(&mut buf) {
+            if n == 0 || tx.send(buf[..n].to_vec()).is_err() {
+                break;
+            }
+        }
+    });
+    // 把标准输入转发给程序
+    let mut input = pty.try_clone_master()?;
+    thread::spawn(move || {
+        let mut buf = [0u8; 1024];
+        let mut stdin = io::stdin();
+        while let Ok(n) = stdin.read(&mut buf) {
+            if n == 0 || input.write_all(&buf[..n]).is_err() {
+                break;
+            }
+        }
+    });
+
+    let frame = Duration::from_secs(1) / fps.max(1);
+    let mut last = Instant::now();
+    let mut dirty = false;
+    loop {
+        match rx.recv_timeout(frame) {
+            Ok(data) => {
+                console.write_all(&data)?;
+                dirty = true;
+            }
+            Err(RecvTimeoutError::Timeout) => {}
+            Err(RecvTimeoutError::Disconnected) => break,
+        }
+        if SWITCH_FONT.swap(false, Ordering::Relaxed) {
+            let next = names
+                .iter()
+                .position(|n| *n == font_name)
+                .map_or(0, |i| (i + 1) % names.len());
+            font_name = names[next].clone();
+            let font = fonts.get(&font_name).unwrap();
+            let (rows, cols) = terminal_size(font.as_ref());
+            console.set_font(font);
+            console
+                .buffer_mut()
+                .resize(rows as usize, Some(cols as usize));
+            pty.resize(rows, cols)?;
+            dirty = true;
+        }
+        // 限制刷新频率，避免占满I2C总线
+        if dirty && last.elapsed() >= frame {
+            console.refresh();
+            last = Instant::now();
+            dirty = false;
+        }
+    }
+    console.refresh();
+    let status = pty.child().wait()?;
+    Ok(status.code().unwrap_or(1))
+}
+
+fn main() {
+    match run() {
+        Ok(code) => process::exit(code),
+        Err(e) => {
+            eprintln!("oled-term: {}", e);
+            process::exit(1);
+        }
+    }
+}
@@ -0,0 +1,261 @@
+//! oled：命令行工具
+//!
+//! 用法:
+//!   oled [--device /dev/i2c-1] [--address 0x3c] [--controller ssd1306|sh1106] 命令 [参数...]
+//!
+//! 命令:
+//!   init                                   初始化屏幕
+//!   clear                                  清屏
+//!   text [--font 8x8] [--x N] [--y N] [--align left|center|right]
+//!        [--valign top|middle|bottom] [--markup] 文字...
+//!   image [--x N] [--y N] 文件|-            显示 PBM 图片（开启 png 特性时也可以是 PNG）
+//!   contrast 0-255                         设置对比度
+//!   invert on|off                          反色显示
+//!   on | off                               打开或关闭显示
+//!   scroll left|right|stop [--start 页] [--end 页] [--interval 0-7]  硬件滚动（仅 SSD1306）
+//!   cat [--font 8x8] [--fps 10]            把标准输入显示在控制台上
+//!
+//! 每次运行都从空白的帧开始，`text` 和 `image` 会覆盖屏幕上原有的内容。
+
+use oled::font::SharedFont;
+use oled::image::Image;
+use oled::{
+    AutoFlush, Console, Controller, Display, HAlign, HEIGHT, OLEDColorMode, Rect, ScrollDirection,
+    VAlign, WIDTH,
+};
+use std::io::{Read, Write};
+use std::sync::mpsc::{self, RecvTimeoutError};
+use std::time::{Duration, Instant};
+use std::{env, fs, io, process, thread};
+
+fn usage() -> ! {
+    eprintln!(
+        "usage: oled [--device DEV] [--address ADDR] [--controller ssd1306|sh1106] COMMAND [ARGS]\n\
+         commands:\n\
+         \x20 init | clear | on | off\n\
+         \x20 text [--font NAME] [--x N] [--y N] [--align left|center|right]\n\
+         \x20      [--valign top|middle|bottom] [--markup] TEXT...\n\
+         \x20 image [--x N] [--y N] FILE|-\n\
+         \x20 contrast VALUE\n\
+         \x20 invert on|off\n\
+         \x20 scroll left|right|stop [--start PAGE] [--end PAGE] [--interval N]\n\
+         \x20 cat [--font NAME] [--fps N]"
+    );
+    process::exit(2);
+}
+
+// 支持十进制和 0x 开头的十六进制
+fn parse_u8(s: &str) -> Result<u8, std::num::ParseIntError> {
+    match s.strip_prefix("0x").or_else(|| s.strip_prefix("0X")) {
+        Some(hex) => u8::from_str_radix(hex, 16),
+        None => s.parse(),
+    }
+}
+
+fn parse_switch(s: &str) -> bool {
+    match s {
+        "on" | "1" | "true" => true,
+        "off" | "0" | "false" => false,
+        _ => usage(),
+    }
+}
+
+fn font(display: &Display, name: &str) -> Result<SharedFont, String> {
+    display
+        .fonts()
+        .get(name)
+        .ok_or_else(|| format!("unknown font {}", name))
+}
+
+fn text(display: &mut Display, args: &[String]) -> Result<(), Box<dyn std::error::Error>> {
+    let mut args = args.iter();
+    let mut font_name = "8x8".to_string();
+    let (mut x, mut y) = (0, 0);
+    let mut align = HAlign::Left;
+    let mut valign = VAlign::Top;
+    let mut markup = false;
+    let mut words = Vec::new();
+    while let Some(arg) = args.next() {
+        let mut value = || args.next().cloned().unwrap_or_else(|| usage());
+        match arg.as_str() {
+            "--font" => font_name = value(),
+            "--x" => x = value().parse()?,
+            "--y" => y = value().parse()?,
+            "--align" => align = value().parse()?,
+            "--valign" => valign = value().parse()?,
+            "--markup" => markup = true,
+            _ => words.push(arg.as_str()),
+        }
+    }
+    let font = font(display, &font_name)?;
+    let s = words.join(" ");
+    display.newframe();
+    if markup {
+        display.markup(x, y, font.as_ref(), &s)?;
+    } else {
+        let rect = Rect::new(
+            x,
+            y,
+            WIDTH as u8 - x.min(WIDTH as u8),
+            HEIGHT as u8 - y.min(HEIGHT as u8),
+        );
+        display.text_aligned(rect, font.as_ref(), &s, align, valign);
+    }
+    display.showframe();
+    Ok(())
+}
+
+fn image(display: &mut Display, args: &[String]) -> Result<(), Box<dyn std::error::Error>> {
+    let mut args = args.iter();
+    let (mut x, mut y) = (0, 0);
+    let mut path = None;
+    while let Some(arg) = args.next() {
+        let mut value = || args.next().cloned().unwrap_or_else(|| usage());
+        match arg.as_str() {
+            "--x" => x = value().parse()?,
+            "--y" => y = value().parse()?,
+            _ => path = Some(arg.clone()),
+        }
+    }
+    let data = match path.as_deref() {
+        Some("-") => {
+            let mut data = Vec::new();
+            io::stdin().read_to_end(&mut data)?;
+            data
+        }
+        Some(path) => fs::read(path)?,
+        None => usage(),
+    };
+    let image = Image::decode(&data)?;
+    display.newframe();
+    image.draw(x, y);
+    display.showframe();
+    Ok(())
+}
+
+fn scroll(display: &mut Display, args: &[String]) -> Result<(), Box<dyn std::error::Error>> {
+    if oled::controller() == Controller::Sh1106 {
+        return Err("sh1106 has no hardware scrolling".into());
+    }
+    let mut args = args.iter();
+    let dir = match args.next().map(String::as_str) {
+        Some("left") => ScrollDirection::Left,
+        Some("right") => ScrollDirection::Right,
+        Some("stop") => {
+            display.stop_scroll();
+            return Ok(());
+        }
+        _ => usage(),
+    };
+    let (mut start, mut end, mut interval) = (0, 7, 7);
+    while let Some(arg) = args.next() {
+        let mut value = || args.next().cloned().unwrap_or_else(|| usage());
+        match arg.as_str() {
+            "--start" => start = value().parse()?,
+            "--end" => end = value().parse()?,
+            "--interval" => interval = value().parse()?,
+            _ => usage(),
+        }
+    }
+    display.start_scroll(dir, start, end, interval);
+    Ok(())
+}
+
+fn cat(display: Display, args: &[String]) -> Result<(), Box<dyn std::error::Error>> {
+    let mut args = args.iter();
+    let mut font_name = "8x8".to_string();
+    let mut fps: u32 = 10;
+    while let Some(arg) = args.next() {
+        let mut value = || args.next().cloned().unwrap_or_else(|| usage());
+        match arg.as_str() {
+            "--font" => font_name = value(),
+            "--fps" => fps = value().parse()?,
+            _ => usage(),
+        }
+    }
+    let font = font(&display, &font_name)?;
+    let mut console = Console::new(display, font).auto_flush(AutoFlush::Never);
+
+    // 标准输入在单独的线程中读取，空闲时也能按时刷新最后的输出
+    let (tx, rx) = mpsc::channel();
+    thread::spawn(move || {
+        let mut buf = [0u8; 4096];
+        let mut stdin = io::stdin();
+        while let Ok(n) = stdin.read(&mut buf) {
+            if n == 0 || tx.send(buf[..n].to_vec()).is_err() {
+                break;
+            }
+        }
+    });
+    let frame = Duration::from_secs(1) / fps.max(1);
+    let mut last = Instant::now();
+    let mut dirty = false;
+    loop {
+        match rx.recv_timeout(frame) {
+            Ok(data) => {
+                console.write_all(&data)?;
+                dirty = true;
+            }
+            Err(RecvTimeoutError::Timeout) => {}
+            Err(RecvTimeoutError::Disconnected) => break,
+        }
+        if dirty && last.elapsed() >= frame {
+            console.refresh();
+            last = Instant::now();
+            dirty = false;
+        }
+    }
+    console.refresh();
+    Ok(())
+}
+
+fn run() -> Result<(), Box<dyn std::error::Error>> {
+    let args: Vec<String> = env::args().skip(1).collect();
+    let mut device = "/dev/i2c-1".to_string();
+    let mut i = 0;
+    while i < args.len() && args[i].starts_with("--") {
+        let value = args.get(i + 1).unwrap_or_else(|| usage());
+        match args[i].as_str() {
+            "--device" => device = value.clone(),
+            "--address" => oled::set_address(parse_u8(value)?),
+            "--controller" => oled::set_controller(match value.as_str() {
+                "ssd1306" => Controller::Ssd1306,
+                "sh1106" => Controller::Sh1106,
+                _ => usage(),
+            }),
+            _ => usage(),
+        }
+        i += 2;
+    }
+    let Some(command) = args.get(i) else { usage() };
+    let rest = &args[i + 1..];
+
+    let mut display = Display::open(&device)?;
+    match command.as_str() {
+        "init" => display.init(),
+        "clear" => display.clear(),
+        "on" => display.set_power(true),
+        "off" => display.set_power(false),
+        "text" => text(&mut display, rest)?,
+        "image" => image(&mut display, rest)?,
+        "contrast" => display.set_contrast(parse_u8(rest.first().unwrap_or_else(|| usage()))?),
+        "invert" => {
+            display.set_color_mode(if parse_switch(rest.first().unwrap_or_else(|| usage())) {
+                OLEDColorMode::ColorReserved
+            } else {
+                OLEDColorMode::ColorNormal
+            })
+        }
+        "scroll" => scroll(&mut display, rest)?,
+        "cat" => cat(display, rest)?,
+        _ => usage(),
+    }
+    Ok(())
+}
+
+fn main() {
+    if let Err(e) = run() {
+        eprintln!("oled: {}", e);
+        process::exit(1);
+    }
+}
@@ -0,0 +1,1008 @@
+use crate::font::SharedFont;
+use crate::text::{TextStyle, wrap_text};
+use std::collections::VecDeque;
+use std::ops::Range;
+use std::sync::OnceLock;
+
+// 默认制表位间隔，以及默认制表位覆盖的列数
+const TAB_WIDTH: usize = 8;
+const TAB_LIMIT: usize = 128;
+// 控制序列最多保留的参数个数，多出的参数忽略
+const MAX_PARAMS: usize = 16;
+
+/// 缓冲区中的一行文字，每个字符带有自己的样式
+#[derive(Clone, Debug, Default)]
+pub struct Line {
+    text: String,
+    styles: Vec<TextStyle>, // 与 text 中的字符一一对应
+    rows: OnceLock<usize>,  // 折行后的可见行数，行内容或折行设置改变时清空
+}
+
+impl PartialEq for Line {
+    fn eq(&self, other: &Self) -> bool {
+        self.text == other.text && self.styles == other.styles
+    }
+}
+
+impl Eq for Line {}
+
+impl Line {
+    pub fn as_str(&self) -> &str {
+        &self.text
+    }
+
+    /// 字符数
+    pub fn len(&self) -> usize {
+        self.styles.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.styles.is_empty()
+    }
+
+    /// 按样式把一行分成若干段 `(文字, 样式)`
+    pub fn runs(&self) -> Vec<(&str, TextStyle)> {
+        self.runs_in(0..self.text.len())
+    }
+
+    // 字节范围 range 内的样式分段
+    fn runs_in(&self, range: Range<usize>) -> Vec<(&str, TextStyle)> {
+        let mut runs = Vec::new();
+        let mut start = range.start;
+        let mut prev = None;
+        for ((pos, _), &style) in self.text.char_indices().zip(&self.styles) {
+            if pos < range.start {
+                continue;
+            }
+            if pos >= range.end {
+                break;
+            }
+            if let Some(p) = prev
+                && p != style
+            {
+                runs.push((&self.text[start..pos], p));
+                start = pos;
+            }
+            prev = Some(style);
+        }
+        if let Some(p) = prev {
+            runs.push((&self.text[start..range.end], p));
+        }
+        runs
+    }
+
+    // 第 col 个字符的字节位置
+    fn byte_index(&self, col: usize) -> usize {
+        self.text
+            .char_indices()
+            .nth(col)
+            .map_or(self.text.len(), |(i, _)| i)
+    }
+
+    // 在第 col 列写入字符，行不够长时用空格补齐
+    fn put(&mut self, col: usize, ch: char, style: TextStyle) {
+        self.rows.take();
+        if col >= self.len() {
+            let fill = col - self.len();
+            self.text.extend(std::iter::repeat_n(' ', fill));
+            self.styles
+                .extend(std::iter::repeat_n(TextStyle::PLAIN, fill));
+            self.text.push(ch);
+            self.styles.push(style);
+        } else {
+            let start = self.byte_index(col);
+            let end = self.byte_index(col + 1);
+            self.text
+                .replace_range(start..end, ch.encode_utf8(&mut [0; 4]));
+            self.styles[col] = style;
+        }
+    }
+
+    // 删除第 col 列及之后的字符
+    fn truncate(&mut self, col: usize) {
+        if col < self.len() {
+            self.rows.take();
+            let i = self.byte_index(col);
+            self.text.truncate(i);
+            self.styles.truncate(col);
+        }
+    }
+
+    // 把前 end 个字符擦成空格
+    fn blank(&mut self, end: usize) {
+        self.rows.take();
+        let end = end.min(self.len());
+        let i = self.byte_index(end);
+        self.text.replace_range(..i, &" ".repeat(end));
+        self.styles[..end].fill(TextStyle::PLAIN);
+    }
+
+    fn clear(&mut self) {
+        self.rows.take();
+        self.text.clear();
+        self.styles.clear();
+    }
+}
+
+/// 视口中的一个可见行：逻辑行按显示宽度折行后的一段
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Row<'a> {
+    line: &'a Line,
+    range: Range<usize>,
+}
+
+impl<'a> Row<'a> {
+    pub fn as_str(&self) -> &'a str {
+        &self.line.text[self.range.clone()]
+    }
+
+    /// 按样式把这一段分成若干段 `(文字, 样式)`
+    pub fn runs(&self) -> Vec<(&'a str, TextStyle)> {
+        self.line.runs_in(self.range.clone())
+    }
+
+    /// 所在的逻辑行
+    pub fn line(&self) -> &'a Line {
+        self.line
+    }
+}
+
+/// 缓冲区中各行的显示顺序
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum LineOrder {
+    /// 最新一行在最上面，`get(0)` 为最新一行。只适合逐行追加的输出，
+    /// 用光标定位的程序在这个顺序下会上下颠倒
+    #[default]
+    NewestFirst,
+    /// 与普通终端一样，最旧的在上面，新行出现在底部并向上滚动；`get(0)` 为最旧一行
+    OldestFirst,
+}
+
+// 转义序列解析状态，跨 push 调用保留
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+enum Escape {
+    #[default]
+    Ground,
+    Esc,                 // 收到 ESC
+    Intermediate,        // ESC 后的中间字节，例如字符集选择 ESC ( B
+    Csi(Vec<u16>, bool), // 控制序列的参数，以及是否为私有序列（ESC [ ?）
+    Osc,                 // 操作系统命令，直到 BEL 或 ESC \ 结束
+    OscEsc,
+}
+
+/// 文字缓冲区，同时是一个简单的 VT100 终端。
+///
+/// 除了 `'\n'`（同时回到行首）和 `'\r'`，还支持：
+/// - 退格、制表符（默认每8列一个制表位，`ESC H` 设置，`CSI g` 清除）和响铃
+/// - 光标移动 `CSI A/B/C/D/E/F/G/d/H/f`，擦除 `CSI J/K`，插入和删除行 `CSI L/M`
+/// - 滚动区域 `CSI r`，`ESC D`（下移一行）、`ESC M`（上移一行）和 `ESC E`，
+///   保存和恢复光标 `ESC 7/8`、`CSI s/u`
+/// - `CSI m` 中的加粗、斜体、下划线、反色和删除线，颜色会被忽略
+///
+/// 其它转义序列会被完整吞掉而不显示。终端的行数为 `new` 的参数，
+/// 光标行号从终端最上面一行起算。
+///
+/// 显示顺序由 `order` 设置，默认最新一行在最上面；`get`、`line` 和 `view`
+/// 都按显示顺序编号。显示终端程序的输出时用 `terminal` 创建，
+/// 终端第一行才会显示在最上面。
+///
+/// 用 `scrollback` 设置历史行数后，滚出终端的行会保留下来，
+/// 可以用 `scroll_up` / `page_up` 等方法移动视口往回翻看。
+///
+/// 用 `wrap` 设置字体和显示宽度后，过长的行会折成多个可见行，
+/// 视口的行数和滚动都按可见行计算；换字体时调用 `set_wrap` 重新折行
+pub struct Buffer {
+    data: VecDeque<Line>, // data[0] 为最新一行，前 screen 行是终端，之后是历史
+    order: LineOrder,
+    len_max: usize,
+    rows: usize,                     // 终端行数
+    screen: usize,                   // 终端中已有的行数，不超过 rows
+    offset: usize,                   // 视口相对最新一行向上滚动的可见行数，0 表示跟随最新输出
+    wrap: Option<(SharedFont, u16)>, // 折行使用的字体和宽度
+    columns: Option<usize>,          // 终端列数，写到行尾时自动换行
+    row: usize,                      // 光标所在行，从最上面一行起算
+    col: usize,                      // 光标所在列
+    style: TextStyle,
+    tabs: Vec<usize>,
+    margins: Option<(usize, usize)>, // 滚动区域的首行和末行，None 表示整个终端
+    saved: Option<(usize, usize, TextStyle)>, // ESC 7 保存的光标位置和样式
+    escape: Escape,
+    bell: bool,
+}
+
+impl Buffer {
+    pub fn new(len_max: usize) -> Self {
+        Buffer {
+            data: VecDeque::new(),
+            order: LineOrder::NewestFirst,
+            len_max: len_max.max(1),
+            rows: len_max.max(1),
+            screen: 0,
+            offset: 0,
+            wrap: None,
+            columns: None,
+            row: 0,
+            col: 0,
+            style: TextStyle::PLAIN,
+            tabs: (TAB_WIDTH..TAB_LIMIT).step_by(TAB_WIDTH).collect(),
+            margins: None,
+            saved: None,
+            escape: Escape::Ground,
+            bell: false,
+        }
+    }
+
+    /// 与普通终端一样新行出现在底部的缓冲区，即 `new(rows).order(LineOrder::OldestFirst)`，
+    /// 光标定位的输出上下顺序正确
+    pub fn terminal(rows: usize) -> Self {
+        Buffer::new(rows).order(LineOrder::OldestFirst)
+    }
+
+    /// 在终端行数之外再保留 `lines` 行历史
+    pub fn scrollback(mut self, lines: usize) -> Self {
+        self.len_max = self.rows + lines;
+        self
+    }
+
+    /// 设置显示顺序
+    pub fn order(mut self, order: LineOrder) -> Self {
+        self.order = order;
+        self
+    }
+
+    pub fn line_order(&self) -> LineOrder {
+        self.order
+    }
+
+    /// 设置终端列数：写满一行后自动换到下一行，光标不会移出最后一列。
+    /// 与 `wrap` 不同，这里的换行与程序看到的终端大小一致，适合全屏程序
+    pub fn columns(mut self, columns: usize) -> Self {
+        self.columns = Some(columns.max(1));
+        self
+    }
+
+    /// 改变终端大小，例如换字体后。行数变少时，光标以下的空行先被删除，
+    /// 仍然放不下时最上面的行进入历史；光标所在行总是留在终端内，
+    /// 光标靠近顶部时删除它下面的行
+    pub fn resize(&mut self, rows: usize, columns: Option<usize>) {
+        let rows = rows.max(1);
+        let history = self.len_max - self.rows;
+        while self.screen > rows && self.row + 1 < self.screen && self.data[0].is_empty() {
+            self.data.pop_front();
+            self.screen -= 1;
+        }
+        if self.screen > rows {
+            // 光标以上的行不够移入历史时，删除光标以下的行
+            let below = self.screen - 1 - self.row;
+            let drop = (self.screen - rows).saturating_sub(self.row).min(below);
+            self.data.drain(..drop);
+            self.screen -= drop;
+            self.row -= self.screen - rows;
+            self.screen = rows;
+        }
+        self.rows = rows;
+        self.margins = None;
+        self.len_max = rows + history;
+        self.data.truncate(self.len_max);
+        self.columns = columns.map(|c| c.max(1));
+        self.col = self.clamp_col(self.col);
+        self.offset = self.offset.min(self.max_offset());
+    }
+
+    // 光标列不超过最后一列
+    fn clamp_col(&self, col: usize) -> usize {
+        self.columns.map_or(col, |c| col.min(c - 1))
+    }
+
+    /// 按字体和显示宽度（像素）折行，`display_buffer` 应使用同一个字体
+    pub fn wrap(mut self, font: SharedFont, width: u16) -> Self {
+        self.set_wrap(font, width);
+        self
+    }
+
+    /// 更换折行的字体或宽度，已有的行会重新折行
+    pub fn set_wrap(&mut self, font: SharedFont, width: u16) {
+        self.wrap = Some((font, width));
+        self.forget_rows();
+        self.offset = self.offset.min(self.max_offset());
+    }
+
+    /// 是否按字体和宽度折行
+    pub fn is_wrapped(&self) -> bool {
+        self.wrap.is_some()
+    }
+
+    /// 取消折行，每个逻辑行只占一个可见行
+    pub fn clear_wrap(&mut self) {
+        self.wrap = None;
+        self.forget_rows();
+        self.offset = self.offset.min(self.max_offset());
+    }
+
+    pub fn push(&mut self, s: String) {
+        // 确保至少有一个字符串在data中
+        if self.data.is_empty() {
+            self.insert_line();
+        }
+
+        for c in s.chars() {
+            match std::mem::take(&mut self.escape) {
+                Escape::Ground => self.input(c),
+                Escape::Esc => self.escape(c),
+                // 中间字节之后的第一个字符结束序列
+                Escape::Intermediate => {
+                    if (' '..='/').contains(&c) {
+                        self.escape = Escape::Intermediate;
+                    }
+                }
+                Escape::Csi(mut params, private) => match c {
+                    '0'..='9' => {
+                        let last = params.last_mut().unwrap();
+                        *last = last
+                            .saturating_mul(10)
+                            .saturating_add(c as u16 - '0' as u16);
+                        self.escape = Escape::Csi(params, private);
+                    }
+                    ';' => {
+                        // 多留一个参数位接收多出的参数，执行时丢弃
+                        if params.len() <= MAX_PARAMS {
+                            params.push(0);
+                        }
+                        self.escape = Escape::Csi(params, private);
+                    }
+                    '?' | '>' | '=' | '<' => self.escape = Escape::Csi(params, true),
+                    '@'..='~' => {
+                        // 私有序列（如隐藏光标）不处理
+                        if !private {
+                            self.csi(c, &params[..params.len().min(MAX_PARAMS)]);
+                        }
+                    }
+                    '\x1b' => self.escape = Escape::Esc,
+                    _ => self.escape = Escape::Csi(params, private),
+                },
+                Escape::Osc => match c {
+                    '\x07' => {}
+                    '\x1b' => self.escape = Escape::OscEsc,
+                    _ => self.escape = Escape::Osc,
+                },
+                Escape::OscEsc => {
+                    if c != '\\' {
+                        self.escape = Escape::Osc;
+                    }
+                }
+            }
+        }
+    }
+
+    // 普通字符和控制字符
+    fn input(&mut self, c: char) {
+        match c {
+            // 遇到换行符，移到下一行并回到行首
+            '\n' | '\x0b' | '\x0c' => self.newline(),
+            // 遇到回车符，将索引重置为0
+            '\r' => self.col = 0,
+            '\x08' => self.col = self.col.saturating_sub(1),
+            '\t' => {
+                if let Some(&stop) = self.tabs.iter().find(|&&t| t > self.col) {
+                    self.col = self.clamp_col(stop);
+                }
+            }
+            '\x07' => self.bell = true,
+            '\x1b' => self.escape = Escape::Esc,
+            c if c.is_control() => {}
+            c => {
+                // 上一个字符写在最后一列时，先自动换行
+                if self.columns.is_some_and(|cols| self.col >= cols) {
+                    self.newline();
+                }
+                let (col, style) = (self.col, self.style);
+                // 查看历史时，当前行折出新的可见行不应让视口移动
+                let anchor = self.offset > 0 && self.wrap.is_some();
+                let before = if anchor { self.wrapped_rows() } else { 0 };
+                self.line_mut().put(col, c, style);
+                if anchor {
+                    self.offset += self.wrapped_rows().saturating_sub(before);
+                }
+                self.col += 1;
+            }
+        }
+    }
+
+    // ESC 之后的字符
+    fn escape(&mut self, c: char) {
+        match c {
+            '[' => self.escape = Escape::Csi(vec![0], false),
+            ']' => self.escape = Escape::Osc,
+            ' '..='/' => self.escape = Escape::Intermediate,
+            'H' => {
+                if let Err(i) = self.tabs.binary_search(&self.col) {
+                    self.tabs.insert(i, self.col);
+                }
+            }
+            'D' => self.line_feed(),
+            'E' => self.newline(),
+            'M' => self.reverse_index(),
+            '7' => self.saved = Some((self.row, self.col, self.style)),
+            '8' => self.restore_cursor(),
+            'c' => self.reset(),
+            _ => {}
+        }
+    }
+
+    // ESC c：清空内容和终端状态，保留行数、历史、顺序、列数和折行设置
+    fn reset(&mut self) {
+        self.data.clear();
+        self.screen = 0;
+        self.offset = 0;
+        self.row = 0;
+        self.col = 0;
+        self.style = TextStyle::PLAIN;
+        self.tabs = (TAB_WIDTH..TAB_LIMIT).step_by(TAB_WIDTH).collect();
+        self.margins = None;
+        self.saved = None;
+        self.insert_line();
+    }
+
+    // 控制序列，参数为0表示默认值
+    fn csi(&mut self, cmd: char, params: &[u16]) {
+        let arg = |i: usize| params.get(i).copied().unwrap_or(0) as usize;
+        let n = arg(0).max(1);
+        match cmd {
+            'A' => self.move_to(self.row.saturating_sub(n), self.col),
+            'B' => self.move_to(self.row + n, self.col),
+            'C' => self.col = self.clamp_col(self.col + n),
+            'D' => self.col = self.col.saturating_sub(n),
+            'E' => self.move_to(self.row + n, 0),
+            'F' => self.move_to(self.row.saturating_sub(n), 0),
+            'G' => self.col = self.clamp_col(n - 1),
+            'd' => self.move_to(n - 1, self.col),
+            'H' | 'f' => self.move_to(n - 1, arg(1).max(1) - 1),
+            'K' => self.erase_line(arg(0)),
+            'J' => self.erase_screen(arg(0)),
+            'm' => self.sgr(params),
+            'L' | 'M' => {
+                let (top, bottom) = self.margins();
+                if (top..=bottom).contains(&self.row) {
+                    for _ in 0..n.min(bottom - self.row + 1) {
+                        self.scroll_region(self.row, bottom, cmd == 'M');
+                    }
+                    self.col = 0;
+                }
+            }
+            'r' => {
+                let top = arg(0).max(1) - 1;
+                let bottom = match arg(1) {
+                    0 => self.rows - 1,
+                    b => (b - 1).min(self.rows - 1),
+                };
+                if top < bottom {
+                    // 覆盖整个终端的区域与不设置相同，滚出的行进入历史
+                    self.margins = (top > 0 || bottom < self.rows - 1).then_some((top, bottom));
+                    self.move_to(0, 0);
+                }
+            }
+            's' => self.saved = Some((self.row, self.col, self.style)),
+            'u' => self.restore_cursor(),
+            'g' => match arg(0) {
+                0 => self.tabs.retain(|&t| t != self.col),
+                3 => self.tabs.clear(),
+                _ => {}
+            },
+            _ => {}
+        }
+    }
+
+    // 设置字符样式
+    fn sgr(&mut self, params: &[u16]) {
+        let mut iter = params.iter();
+        while let Some(&p) = iter.next() {
+            match p {
+                0 => self.style = TextStyle::PLAIN,
+                1 => self.style.bold = true,
+                3 => self.style.italic = true,
+                4 => self.style.underline = true,
+                7 => self.style.invert = true,
+                9 => self.style.strikethrough = true,
+                21 | 22 => self.style.bold = false,
+                23 => self.style.italic = false,
+                24 => self.style.underline = false,
+                27 => self.style.invert = false,
+                29 => self.style.strikethrough = false,
+                // 256色和真彩色带有额外参数，一起跳过
+                38 | 48 | 58 => match iter.next() {
+                    Some(5) => {
+                        iter.next();
+                    }
+                    Some(2) => {
+                        iter.nth(2);
+                    }
+                    _ => {}
+                },
+                _ => {}
+            }
+        }
+    }
+
+    fn erase_line(&mut self, mode: usize) {
+        let col = self.col;
+        let line = self.line_mut();
+        match mode {
+            0 => line.truncate(col),
+            1 => line.blank(col + 1),
+            2 => line.clear(),
+            _ => {}
+        }
+    }
+
+    // 擦除终端中的行，历史不受影响；模式3只清除历史
+    fn erase_screen(&mut self, mode: usize) {
+        let index = self.index(self.row);
+        match mode {
+            0 => {
+                self.erase_line(0);
+                self.data.range_mut(..index).for_each(Line::clear);
+            }
+            1 => {
+                self.erase_line(1);
+                self.data
+                    .range_mut(index + 1..self.screen)
+                    .for_each(Line::clear);
+            }
+            2 => self.data.range_mut(..self.screen).for_each(Line::clear),
+            3 => {
+                self.data.truncate(self.screen);
+                self.offset = 0;
+            }
+            _ => {}
+        }
+    }
+
+    // 光标所在行在 data 中的位置
+    fn index(&self, row: usize) -> usize {
+        self.screen - 1 - row
+    }
+
+    // 在头部添加新行，终端已满时最上面一行进入历史
+    fn insert_line(&mut self) {
+        self.data.push_front(Line::default());
+        if self.screen < self.rows {
+            self.screen += 1;
+        }
+        // 视口不在最新处时保持显示同样的内容
+        if self.offset > 0 {
+            self.offset += 1;
+        }
+        // 保证最大行数
+        if self.data.len() > self.len_max {
+            self.data.pop_back();
+        }
+        self.offset = self.offset.min(self.max_offset());
+    }
+
+    fn line_mut(&mut self) -> &mut Line {
+        let index = self.index(self.row);
+        &mut self.data[index]
+    }
+
+    // 移动光标，行号超出终端行数时停在最后一行，并补齐中间的空行
+    fn move_to(&mut self, row: usize, col: usize) {
+        self.row = row.min(self.rows - 1);
+        self.col = self.clamp_col(col);
+        while self.screen <= self.row {
+            self.insert_line();
+        }
+    }
+
+    fn newline(&mut self) {
+        self.col = 0;
+        self.line_feed();
+    }
+
+    // 光标下移一行，列不变；在滚动区域的末行时区域向上滚动
+    fn line_feed(&mut self) {
+        match self.margins {
+            Some((top, bottom)) if self.row == bottom => self.scroll_region(top, bottom, true),
+            _ if self.row + 1 < self.rows => self.move_to(self.row + 1, self.col),
+            // 设置了滚动区域时，区域以下的最后一行不滚动
+            Some(_) => {}
+            // 已在最后一行，终端向上滚动
+            None => self.insert_line(),
+        }
+    }
+
+    // 光标上移一行；在滚动区域的首行时区域向下滚动
+    fn reverse_index(&mut self) {
+        let (top, bottom) = self.margins();
+        if self.row == top {
+            self.scroll_region(top, bottom, false);
+        } else {
+            self.move_to(self.row.saturating_sub(1), self.col);
+        }
+    }
+
+    // 滚动区域，没有设置时为整个终端
+    fn margins(&self) -> (usize, usize) {
+        self.margins.unwrap_or((0, self.rows - 1))
+    }
+
+    // 在终端的 top..=bottom 行之间滚动一行，滚出区域的行直接丢弃，不进入历史
+    fn scroll_region(&mut self, top: usize, bottom: usize, up: bool) {
+        while self.screen <= bottom {
+            self.insert_line();
+        }
+        let (first, last) = (self.index(top), self.index(bottom));
+        if up {
+            self.data.remove(first);
+            self.data.insert(last, Line::default());
+        } else {
+            self.data.remove(last);
+            self.data.insert(first, Line::default());
+        }
+    }
+
+    fn restore_cursor(&mut self) {
+        let (row, col, style) = self.saved.unwrap_or((0, 0, TextStyle::PLAIN));
+        self.style = style;
+        self.move_to(row, col);
+    }
+
+    /// 按显示顺序第 `index` 行的文字：`NewestFirst` 时0为最新一行，
+    /// `OldestFirst` 时0为最旧一行（含历史）
+    pub fn get(&self, index: usize) -> Option<&str> {
+        self.line(index).map(Line::as_str)
+    }
+
+    /// 按显示顺序第 `index` 行的文字和样式，编号同 `get`
+    pub fn line(&self, index: usize) -> Option<&Line> {
+        match self.order {
+            LineOrder::NewestFirst => self.data.get(index),
+            LineOrder::OldestFirst => self
+                .data
+                .len()
+                .checked_sub(index)
+                .and_then(|n| self.data.get(n.checked_sub(1)?)),
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.data.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.data.is_empty()
+    }
+
+    /// 终端行数，也是视口的行数
+    pub fn rows(&self) -> usize {
+        self.rows
+    }
+
+    // 一个逻辑行折行后每个可见行的字节范围，至少有一行
+    fn wrap_line(&self, line: &Line) -> Vec<Range<usize>> {
+        match &self.wrap {
+            Some((font, width)) if !line.is_empty() => wrap_text(font.as_ref(), &line.text, *width),
+            _ => std::iter::once(0..line.text.len()).collect(),
+        }
+    }
+
+    // 一个逻辑行占用的可见行数，折行结果缓存在行里
+    fn row_count(&self, line: &Line) -> usize {
+        match self.wrap {
+            Some(_) => *line.rows.get_or_init(|| self.wrap_line(line).len()),
+            None => 1,
+        }
+    }
+
+    // 折行设置改变后清空所有行的缓存
+    fn forget_rows(&mut self) {
+        for line in &mut self.data {
+            line.rows.take();
+        }
+    }
+
+    // 光标所在行占用的可见行数
+    fn wrapped_rows(&self) -> usize {
+        self.row_count(&self.data[self.index(self.row)])
+    }
+
+    /// 视口中的可见行，按显示顺序从上到下排列，同一逻辑行折出的行保持原来的顺序
+    pub fn view(&self) -> Vec<Row<'_>> {
+        let oldest_first = self.order == LineOrder::OldestFirst;
+        // 从最新的可见行开始数，整行跳过向上滚动的部分，只折需要显示的行
+        let mut skip = self.offset;
+        let mut lines = self.data.iter().peekable();
+        while let Some(line) = lines.peek() {
+            let n = self.row_count(line);
+            if n > skip {
+                break;
+            }
+            skip -= n;
+            lines.next();
+        }
+        let mut rows: Vec<Row> = lines
+            .flat_map(|line| {
+                let mut ranges = self.wrap_line(line);
+                if oldest_first {
+                    ranges.reverse();
+                }
+                ranges.into_iter().map(move |range| Row { line, range })
+            })
+            .skip(skip)
+            .take(self.rows)
+            .collect();
+        if oldest_first {
+            rows.reverse();
+        }
+        rows
+    }
+
+    /// 全部可见行数（含历史）
+    pub fn total_rows(&self) -> usize {
+        match self.wrap {
+            Some(_) => self.data.iter().map(|l| self.row_count(l)).sum(),
+            None => self.data.len(),
+        }
+    }
+
+    /// 视口向上滚动的可见行数，0 表示跟随最新输出
+    pub fn offset(&self) -> usize {
+        self.offset
+    }
+
+    fn max_offset(&self) -> usize {
+        self.total_rows().saturating_sub(self.rows)
+    }
+
+    /// 视口是否停在最新输出处
+    pub fn is_live(&self) -> bool {
+        self.offset == 0
+    }
+
+    /// 向历史方向滚动 `n` 行
+    pub fn scroll_up(&mut self, n: usize) {
+        self.offset = (self.offset + n).min(self.max_offset());
+    }
+
+    /// 向最新输出方向滚动 `n` 行
+    pub fn scroll_down(&mut self, n: usize) {
+        self.offset = self.offset.saturating_sub(n);
+    }
+
+    pub fn page_up(&mut self) {
+        self.scroll_up(self.rows);
+    }
+
+    pub fn page_down(&mut self) {
+        self.scroll_down(self.rows);
+    }
+
+    /// 滚动到最旧的一行
+    pub fn scroll_home(&mut self) {
+        self.offset = self.max_offset();
+    }
+
+    /// 回到最新输出
+    pub fn scroll_end(&mut self) {
+        self.offset = 0;
+    }
+
+    /// 光标位置 `(行, 列)`，行号从最上面一行起算
+    pub fn cursor(&self) -> (usize, usize) {
+        (self.row, self.col)
+    }
+
+    /// 自上次调用以来是否收到过响铃（BEL）
+    pub fn take_bell(&mut self) -> bool {
+        std::mem::take(&mut self.bell)
+    }
+
+    pub fn clear(&mut self) {
+        self.data.clear();
+        self.screen = 0;
+        self.offset = 0;
+        self.row = 0;
+        self.col = 0;
+        self.style = TextStyle::PLAIN;
+        self.margins = None;
+        self.saved = None;
+        self.escape = Escape::Ground;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn lines(buffer: &Buffer) -> Vec<&str> {
+        (0..buffer.len()).filter_map(|i| buffer.get(i)).collect()
+    }
+
+    fn view(buffer: &Buffer) -> Vec<&str> {
+        buffer.view().iter().map(Row::as_str).collect()
+    }
+
+    #[test]
+    fn terminal_keeps_the_first_row_on_top() {
+        let mut buffer = Buffer::terminal(3);
+        buffer.push("\x1b[3;1Hthird\x1b[1;1Hfirst\x1b[2;1Hsecond".to_string());
+        assert_eq!(view(&buffer), ["first", "second", "third"]);
+        assert_eq!(lines(&buffer), ["first", "second", "third"]);
+        // 默认顺序下同样的输出上下颠倒
+        let mut buffer = Buffer::new(3);
+        buffer.push("\x1b[3;1Hthird\x1b[1;1Hfirst\x1b[2;1Hsecond".to_string());
+        assert_eq!(view(&buffer), ["third", "second", "first"]);
+    }
+
+    #[test]
+    fn wrapped_rows_follow_edits_and_font() {
+        use crate::font::FONT8X8;
+        use std::sync::Arc;
+
+        let font: SharedFont = Arc::new(FONT8X8);
+        let mut buffer = Buffer::new(2)
+            .scrollback(10)
+            .order(LineOrder::OldestFirst)
+            .wrap(font.clone(), 32);
+        buffer.push("abcdefghijkl\nwxyz".to_string());
+        assert_eq!(buffer.total_rows(), 4);
+        assert_eq!(view(&buffer), ["kl", "wxyz"]);
+        buffer.scroll_up(2);
+        assert_eq!(view(&buffer), ["abcde", "fghij"]);
+        buffer.scroll_up(1);
+        assert_eq!(view(&buffer), ["abcde", "fghij"]);
+
+        buffer.scroll_end();
+        buffer.push("\x1b[A\r\x1b[Kab".to_string());
+        assert_eq!(buffer.total_rows(), 2);
+        assert_eq!(view(&buffer), ["ab", "wxyz"]);
+
+        buffer.set_wrap(font, 16);
+        assert_eq!(buffer.total_rows(), 3);
+        assert_eq!(view(&buffer), ["wx", "yz"]);
+        buffer.clear_wrap();
+        assert_eq!(buffer.total_rows(), 2);
+    }
+
+    #[test]
+    fn resize_moves_top_lines_into_history() {
+        let mut buffer = Buffer::new(4).scrollback(10).order(LineOrder::OldestFirst);
+        buffer.push("a\nb\nc\nd".to_string());
+        buffer.resize(2, None);
+        assert_eq!(buffer.cursor(), (1, 1));
+        assert_eq!(lines(&buffer), ["a", "b", "c", "d"]);
+        buffer.push("!".to_string());
+        assert_eq!(buffer.get(3), Some("d!"));
+    }
+
+    #[test]
+    fn resize_with_cursor_above_filled_bottom_line() {
+        let mut buffer = Buffer::new(4).scrollback(10).order(LineOrder::OldestFirst);
+        buffer.push("a\nb\nc\nd\x1b[2;1H".to_string());
+        buffer.resize(2, None);
+        assert_eq!(buffer.cursor(), (0, 0));
+        assert_eq!(lines(&buffer), ["a", "b", "c"]);
+        buffer.push("B".to_string());
+        assert_eq!(lines(&buffer), ["a", "B", "c"]);
+
+        buffer.push("\x1b[1;1H".to_string());
+        buffer.resize(1, None);
+        assert_eq!(buffer.cursor(), (0, 0));
+        assert_eq!(lines(&buffer), ["a", "B"]);
+    }
+
+    #[test]
+    fn resize_drops_empty_lines_below_cursor() {
+        let mut buffer = Buffer::new(4).order(LineOrder::OldestFirst);
+        buffer.push("a\x1b[4;1H\x1b[1;2H".to_string());
+        buffer.resize(2, Some(10));
+        assert_eq!(buffer.cursor(), (0, 1));
+        assert_eq!(lines(&buffer), ["a", ""]);
+    }
+
+    #[test]
+    fn scroll_region() {
+        let mut buffer = Buffer::new(4).scrollback(10).order(LineOrder::OldestFirst);
+        buffer.push("a\nb\nc\nd".to_string());
+        // 第2、3行为滚动区域，在末行换行只滚动区域，第1行和第4行不动
+        buffer.push("\x1b[2;3r".to_string());
+        assert_eq!(buffer.cursor(), (0, 0));
+        buffer.push("\x1b[3;1H\nx".to_string());
+        assert_eq!(lines(&buffer), ["a", "c", "x", "d"]);
+        // 在区域首行反向换行，区域向下滚动
+        buffer.push("\x1b[2;1H\x1bMy".to_string());
+        assert_eq!(lines(&buffer), ["a", "y", "c", "d"]);
+        // 插入和删除行也限制在区域内
+        buffer.push("\x1b[2;1H\x1b[M".to_string());
+        assert_eq!(lines(&buffer), ["a", "c", "", "d"]);
+        buffer.push("\x1b[L".to_string());
+        assert_eq!(lines(&buffer), ["a", "", "c", "d"]);
+        // 取消滚动区域后，滚出的行进入历史
+        buffer.push("\x1b[r\x1b[4;1H\ne".to_string());
+        assert_eq!(lines(&buffer), ["a", "", "c", "d", "e"]);
+    }
+
+    #[test]
+    fn index_and_saved_cursor() {
+        let mut buffer = Buffer::new(3).order(LineOrder::OldestFirst);
+        buffer.push("ab\x1b7\x1b[1mc\x1b[3;1H\x1b8d".to_string());
+        // 恢复光标时样式也恢复为保存时的普通样式
+        assert_eq!(buffer.cursor(), (0, 3));
+        assert_eq!(buffer.line(0).unwrap().runs(), [("abd", TextStyle::PLAIN)]);
+        buffer.push("\x1b[0m\x1b[sz\x1bDw\x1b[u!".to_string());
+        assert_eq!(lines(&buffer), ["abd!", "    w", ""]);
+        // 在首行反向换行，在顶部插入空行
+        buffer.push("\x1b[1;1H\x1bM".to_string());
+        assert_eq!(lines(&buffer), ["", "abd!", "    w"]);
+    }
+
+    #[test]
+    fn full_reset_keeps_configuration() {
+        let mut buffer = Buffer::terminal(3).columns(4);
+        buffer.push("\x1b[1mold\x1b[2;3r\x1b[3;1Hx\x1bc".to_string());
+        assert_eq!(buffer.cursor(), (0, 0));
+        buffer.push("first\nsecond".to_string());
+        // 仍然是新行在下，且按 4 列自动换行
+        assert_eq!(lines(&buffer), ["t", "seco", "nd"]);
+        assert_eq!(buffer.line_order(), LineOrder::OldestFirst);
+        assert_eq!(buffer.line(0).unwrap().runs(), [("t", TextStyle::PLAIN)]);
+    }
+
+    #[test]
+    fn extra_csi_params_are_ignored() {
+        let mut buffer = Buffer::terminal(3);
+        let params = ";".repeat(100) + "1";
+        buffer.push(format!("\x1b[2;3{params}Hx"));
+        assert_eq!(buffer.cursor(), (1, 3));
+        assert_eq!(lines(&buffer), ["", "  x"]);
+    }
+
+    #[test]
+    fn viewport_scrolls_through_history() {
+        let mut buffer = Buffer::new(3).scrollback(5);
+        buffer.push("1\n2\n3\n4\n5\n6".to_string());
+        assert!(buffer.is_live());
+        assert_eq!(view(&buffer), ["6", "5", "4"]);
+
+        buffer.scroll_up(1);
+        assert!(!buffer.is_live());
+        assert_eq!(view(&buffer), ["5", "4", "3"]);
+        // 最多滚到最旧的一行在视口底部
+        buffer.page_up();
+        assert_eq!(buffer.offset(), 3);
+        assert_eq!(view(&buffer), ["3", "2", "1"]);
+        buffer.scroll_up(100);
+        assert_eq!(buffer.offset(), 3);
+
+        buffer.page_down();
+        assert!(buffer.is_live());
+        buffer.scroll_home();
+        assert_eq!(view(&buffer), ["3", "2", "1"]);
+        buffer.scroll_down(1);
+        assert_eq!(view(&buffer), ["4", "3", "2"]);
+        buffer.scroll_end();
+        assert!(buffer.is_live());
+        assert_eq!(view(&buffer), ["6", "5", "4"]);
+    }
+
+    #[test]
+    fn get_follows_the_line_order() {
+        let text = "old\nmid\nnew";
+        let mut buffer = Buffer::new(3);
+        buffer.push(text.to_string());
+        assert_eq!(
+            [buffer.get(0), buffer.get(2), buffer.get(3)],
+            [Some("new"), Some("old"), None]
+        );
+        let mut buffer = Buffer::new(3).order(LineOrder::OldestFirst);
+        buffer.push(text.to_string());
+        assert_eq!(
+            [buffer.get(0), buffer.get(2), buffer.get(3)],
+            [Some("old"), Some("new"), None]
+        );
+        // 历史中的行同样按显示顺序编号
+        let mut buffer = Buffer::terminal(2).scrollback(2);
+        buffer.push("a\nb\nc\nd\ne".to_string());
+        assert_eq!(lines(&buffer), ["b", "c", "d", "e"]);
+        assert_eq!(buffer.get(usize::MAX), None);
+    }
+}
@@ -0,0 +1,225 @@
+use crate::font::SharedFont;
+use crate::{Buffer, Display, HEIGHT, WIDTH};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+use std::{fmt, io, thread};
+
+/// 控制台何时自动刷新到屏幕
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum AutoFlush {
+    /// 只在调用 `refresh` 或 `io::Write::flush` 时刷新
+    Never,
+    /// 写入的内容包含换行时刷新
+    #[default]
+    Newline,
+    /// 写入时距上次刷新超过指定时间才刷新，避免频繁占用I2C总线。
+    /// 停止写入后剩下的内容由 `Console::tick` 显示，`Console::share` 会在后台定时调用
+    Interval(Duration),
+}
+
+/// 文字控制台：把 `Buffer` 和显示屏组合在一起，实现了 `fmt::Write` 和 `io::Write`，
+/// 可以直接作为 `write!` / `writeln!` 的输出目标
+pub struct Console {
+    display: Display,
+    buffer: Buffer,
+    font: SharedFont,
+    auto_flush: AutoFlush,
+    last_flush: Instant,
+    dirty: bool,      // 有写入后还没显示的内容
+    pending: Vec<u8>, // io::Write 收到的不完整的 UTF-8 字节
+}
+
+impl Console {
+    /// 按字体高度计算行数，新行出现在底部，按屏幕宽度折行
+    pub fn new(display: Display, font: SharedFont) -> Self {
+        let rows = HEIGHT / font.get_font_height().max(1) as usize;
+        let buffer = Buffer::terminal(rows).wrap(font.clone(), WIDTH as u16);
+        Console::with_buffer(display, buffer, font)
+    }
+
+    /// 使用自己配置的缓冲区，例如带历史或不同的行顺序
+    pub fn with_buffer(display: Display, buffer: Buffer, font: SharedFont) -> Self {
+        Console {
+            display,
+            buffer,
+            font,
+            auto_flush: AutoFlush::Newline,
+            last_flush: Instant::now(),
+            dirty: false,
+            pending: Vec::new(),
+        }
+    }
+
+    /// 设置自动刷新方式，默认遇到换行时刷新
+    pub fn auto_flush(mut self, auto_flush: AutoFlush) -> Self {
+        self.auto_flush = auto_flush;
+        self
+    }
+
+    /// 写入文字，支持 `Buffer` 的所有转义序列
+    pub fn write_text(&mut self, s: &str) {
+        self.buffer.push(s.to_string());
+        self.dirty = true;
+        let due = match self.auto_flush {
+            AutoFlush::Never => false,
+            AutoFlush::Newline => s.contains('\n'),
+            AutoFlush::Interval(interval) => self.last_flush.elapsed() >= interval,
+        };
+        if due {
+            self.refresh();
+        }
+    }
+
+    /// 把缓冲区绘制到屏幕
+    pub fn refresh(&mut self) {
+        self.display
+            .display_buffer(&self.buffer, 0, 0, self.font.as_ref());
+        self.last_flush = Instant::now();
+        self.dirty = false;
+    }
+
+    /// `Interval` 模式下，间隔已到时显示还没刷新的内容。返回距离下一次需要调用的时间，
+    /// 没有待显示的内容时返回 `None`
+    pub fn tick(&mut self) -> Option<Duration> {
+        let AutoFlush::Interval(interval) = self.auto_flush else {
+            return None;
+        };
+        if !self.dirty {
+            return None;
+        }
+        let elapsed = self.last_flush.elapsed();
+        if elapsed >= interval {
+            self.refresh();
+            None
+        } else {
+            Some(interval - elapsed)
+        }
+    }
+
+    /// 放到互斥锁中，可以在多个线程中写入。`Interval` 模式下同时启动后台线程调用 `tick`，
+    /// 控制台被丢弃后线程自动退出
+    pub fn share(self) -> Arc<Mutex<Console>> {
+        let auto_flush = self.auto_flush;
+        let console = Arc::new(Mutex::new(self));
+        if let AutoFlush::Interval(interval) = auto_flush {
+            let weak = Arc::downgrade(&console);
+            thread::spawn(move || {
+                while let Some(console) = weak.upgrade() {
+                    let wait = console.lock().ok().and_then(|mut c| c.tick());
+                    drop(console);
+                    thread::sleep(wait.unwrap_or(interval));
+                }
+            });
+        }
+        console
+    }
+
+    /// 更换字体；缓冲区折行时按新字体重新折行，按列数换行的终端缓冲区不受影响
+    pub fn set_font(&mut self, font: SharedFont) {
+        if self.buffer.is_wrapped() {
+            self.buffer.set_wrap(font.clone(), WIDTH as u16);
+        }
+        self.font = font;
+    }
+
+    pub fn font(&self) -> &SharedFont {
+        &self.font
+    }
+
+    pub fn buffer(&self) -> &Buffer {
+        &self.buffer
+    }
+
+    pub fn buffer_mut(&mut self) -> &mut Buffer {
+        &mut self.buffer
+    }
+
+    pub fn display_mut(&mut self) -> &mut Display {
+        &mut self.display
+    }
+
+    pub fn into_inner(self) -> (Display, Buffer) {
+        (self.display, self.buffer)
+    }
+}
+
+impl fmt::Write for Console {
+    fn write_str(&mut self, s: &str) -> fmt::Result {
+        self.write_text(s);
+        Ok(())
+    }
+}
+
+impl io::Write for Console {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.pending.extend_from_slice(buf);
+        // 末尾被截断的多字节字符留到下次写入，无效的字节替换为 U+FFFD
+        let valid = match std::str::from_utf8(&self.pending) {
+            Ok(_) => self.pending.len(),
+            Err(e) if e.error_len().is_none() => e.valid_up_to(),
+            Err(_) => self.pending.len(),
+        };
+        let bytes: Vec<u8> = self.pending.drain(..valid).collect();
+        self.write_text(&String::from_utf8_lossy(&bytes));
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.refresh();
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::font::FONT8X8;
+    use crate::frame_lock;
+    use crate::image::Image;
+
+    fn blank() -> Image {
+        Image::new(WIDTH, HEIGHT)
+    }
+
+    #[test]
+    fn interval_shows_trailing_output() {
+        let _lock = frame_lock();
+        let interval = Duration::from_millis(30);
+        let mut console = Console::new(Display::simulated(), Arc::new(FONT8X8))
+            .auto_flush(AutoFlush::Interval(interval));
+        crate::newframe();
+        console.write_text("hello");
+        assert_eq!(Image::from_frame(), blank());
+        assert!(console.tick().is_some_and(|wait| wait <= interval));
+        thread::sleep(interval);
+        assert_eq!(console.tick(), None);
+        assert_ne!(Image::from_frame(), blank());
+        assert_eq!(console.tick(), None);
+    }
+
+    #[test]
+    fn shared_console_flushes_in_background() {
+        let _lock = frame_lock();
+        let console = Console::new(Display::simulated(), Arc::new(FONT8X8))
+            .auto_flush(AutoFlush::Interval(Duration::from_millis(10)))
+            .share();
+        crate::newframe();
+        console.lock().unwrap().write_text("hello");
+        let start = Instant::now();
+        while Image::from_frame() == blank() {
+            assert!(start.elapsed() < Duration::from_secs(5));
+            thread::sleep(Duration::from_millis(5));
+        }
+    }
+
+    #[test]
+    fn set_font_keeps_terminal_columns() {
+        let buffer = Buffer::terminal(8).columns(21);
+        let mut console = Console::with_buffer(Display::simulated(), buffer, Arc::new(FONT8X8));
+        console.set_font(Arc::new(crate::font::FONT16X16));
+        assert!(!console.buffer().is_wrapped());
+        let mut console = Console::new(Display::simulated(), Arc::new(FONT8X8));
+        console.set_font(Arc::new(crate::font::FONT16X16));
+        assert!(console.buffer().is_wrapped());
+    }
+}
@@ -0,0 +1,865 @@
+//! 显示守护进程：独占屏幕，通过 Unix 套接字接受多个客户端的绘制请求
+//!
+//! 协议按行发送（每行最长 64KiB），每条请求回复一行 `ok` 或 `err 原因`。坐标都相对于区域左上角，
+//! 超出区域的部分被裁掉：
+//!
+//! ```text
+//! region 名称 X Y W H                 定义或修改区域，预定义的 screen 是整个屏幕
+//! clear [区域]                        清空区域，省略时清空整个屏幕
+//! text 区域 [font=8x8] [align=left|center|right] [valign=top|middle|bottom] 文字
+//!                                     清空区域后绘制文字，\n 换行，\\ 表示反斜杠
+//! pixel 区域 X Y [off]
+//! line 区域 X0 Y0 X1 Y1 [off]
+//! rect 区域 X Y W H [fill] [off]
+//! circle 区域 X Y R [off]
+//! image 区域 X Y 长度                 后面紧跟指定长度的 PBM 数据
+//! frame 长度                          后面紧跟 128x64 的 PBM 数据，替换整个屏幕
+//! contrast 0-255
+//! power on|off
+//! invert on|off
+//! flush                               立即刷新屏幕
+//! ```
+//!
+//! 坐标可以是任意整数（包括 `image`），`rect` 的宽高和 `circle` 的半径不能超过屏幕。
+//! `image` / `frame` 请求出错时仍按长度跳过后面的数据，长度无法解析时断开连接。
+//! 开启 `png` 特性时 `image` 和 `frame` 也接受 PNG 数据。绘制请求只修改帧缓冲，
+//! 由守护进程按刷新间隔统一发送到屏幕。
+
+use crate::image::Image;
+use crate::region::{Canvas, Layout};
+use crate::{Display, HAlign, HEIGHT, OLEDColorMode, Rect, VAlign, WIDTH};
+use std::io::{self, BufRead, BufReader, Read, Write};
+use std::os::unix::net::{UnixListener, UnixStream};
+use std::sync::{Arc, Mutex, Once};
+use std::thread;
+use std::time::Duration;
+
+// 单个 image / frame 请求的数据上限
+const MAX_PAYLOAD: usize = 1 << 20;
+// 请求行的长度上限，不含 image / frame 的数据
+const MAX_LINE: usize = 1 << 16;
+
+/// 一条绘制或控制请求
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Request {
+    Region(String, Rect),
+    Clear(Option<String>),
+    Text {
+        region: String,
+        font: String,
+        align: HAlign,
+        valign: VAlign,
+        text: String,
+    },
+    Pixel {
+        region: String,
+        x: i32,
+        y: i32,
+        color: bool,
+    },
+    Line {
+        region: String,
+        from: (i32, i32),
+        to: (i32, i32),
+        color: bool,
+    },
+    Rect {
+        region: String,
+        x: i32,
+        y: i32,
+        w: i32,
+        h: i32,
+        fill: bool,
+        color: bool,
+    },
+    Circle {
+        region: String,
+        x: i32,
+        y: i32,
+        r: i32,
+        color: bool,
+    },
+    /// PBM 或 PNG 数据
+    Image {
+        region: String,
+        x: i32,
+        y: i32,
+        data: Vec<u8>,
+    },
+    /// 整屏的 PBM 或 PNG 数据
+    Frame(Vec<u8>),
+    Contrast(u8),
+    Power(bool),
+    Invert(bool),
+    Flush,
+}
+
+// 取出下一个空白分隔的词
+fn token<'a>(rest: &mut &'a str) -> Option<&'a str> {
+    let s = rest.trim_start();
+    if s.is_empty() {
+        return None;
+    }
+    let end = s.find(char::is_whitespace).unwrap_or(s.len());
+    *rest = &s[end..];
+    Some(&s[..end])
+}
+
+fn required<'a>(rest: &mut &'a str, what: &str) -> Result<&'a str, String> {
+    token(rest).ok_or_else(|| format!("missing {}", what))
+}
+
+fn number<T: std::str::FromStr>(rest: &mut &str, what: &str) -> Result<T, String> {
+    let s = required(rest, what)?;
+    s.parse().map_err(|_| format!("bad {}: {}", what, s))
+}
+
+fn switch(s: &str) -> Result<bool, String> {
+    match s {
+        "on" | "1" | "true" => Ok(true),
+        "off" | "0" | "false" => Ok(false),
+        _ => Err(format!("expected on or off: {}", s)),
+    }
+}
+
+// 行尾的 fill / off 标志
+fn flags(rest: &mut &str) -> Result<(bool, bool), String> {
+    let (mut fill, mut color) = (false, true);
+    while let Some(flag) = token(rest) {
+        match flag {
+            "fill" => fill = true,
+            "off" => color = false,
+            "on" => color = true,
+            _ => return Err(format!("unknown flag {}", flag)),
+        }
+    }
+    Ok((fill, color))
+}
+
+fn unescape(s: &str) -> String {
+    let mut out = String::with_capacity(s.len());
+    let mut chars = s.chars();
+    while let Some(ch) = chars.next() {
+        if ch != '\\' {
+            out.push(ch);
+            continue;
+        }
+        match chars.next() {
+            Some('n') => out.push('\n'),
+            Some(other) => out.push(other),
+            None => out.push('\\'),
+        }
+    }
+    out
+}
+
+fn payload(rest: &mut &str) -> Result<Vec<u8>, String> {
+    let len: usize = number(rest, "length")?;
+    if len > MAX_PAYLOAD {
+        return Err(format!("payload too large: {}", len));
+    }
+    Ok(vec![0; len])
+}
+
+impl Request {
+    /// 解析一行请求。`image` 和 `frame` 的数据先按长度分配好，由调用者用
+    /// `payload_mut` 填入
+    pub fn parse(line: &str) -> Result<Request, String> {
+        let mut rest = line.trim_end_matches(['\r', '\n']);
+        let rest = &mut rest;
+        let command = required(rest, "command")?;
+        let request = match command {
+            "region" => {
+                let name = required(rest, "region")?.to_string();
+                let rect = Rect::new(
+                    number(rest, "x")?,
+                    number(rest, "y")?,
+                    number(rest, "width")?,
+                    number(rest, "height")?,
+                );
+                Request::Region(name, rect)
+            }
+            "clear" => Request::Clear(token(rest).map(String::from)),
+            "text" => {
+                let region = required(rest, "region")?.to_string();
+                let mut font = "8x8".to_string();
+                let mut align = HAlign::Left;
+                let mut valign = VAlign::Top;
+                // 选项之后直到行尾都是文字
+                loop {
+                    let before = *rest;
+                    let Some((key, value)) = token(rest).and_then(|t| t.split_once('=')) else {
+                        *rest = before;
+                        break;
+                    };
+                    match key {
+                        "font" => font = value.to_string(),
+                        "align" => align = value.parse()?,
+                        "valign" => valign = value.parse()?,
+                        _ => {
+                            *rest = before;
+                            break;
+                        }
+                    }
+                }
+                let text = unescape(rest.trim_start());
+                Request::Text {
+                    region,
+                    font,
+                    align,
+                    valign,
+                    text,
+                }
+            }
+            "pixel" => {
+                let region = required(rest, "region")?.to_string();
+                let (x, y) = (number(rest, "x")?, number(rest, "y")?);
+                let (_, color) = flags(rest)?;
+                Request::Pixel {
+                    region,
+                    x,
+                    y,
+                    color,
+                }
+            }
+            "line" => {
+                let region = required(rest, "region")?.to_string();
+                let from = (number(rest, "x0")?, number(rest, "y0")?);
+                let to = (number(rest, "x1")?, number(rest, "y1")?);
+                let (_, color) = flags(rest)?;
+                Request::Line {
+                    region,
+                    from,
+                    to,
+                    color,
+                }
+            }
+            "rect" => {
+                let region = required(rest, "region")?.to_string();
+                let (x, y) = (number(rest, "x")?, number(rest, "y")?);
+                let (w, h) = (number(rest, "width")?, number(rest, "height")?);
+                let (fill, color) = flags(rest)?;
+                Request::Rect {
+                    region,
+                    x,
+                    y,
+                    w,
+                    h,
+                    fill,
+                    color,
+                }
+            }
+            "circle" => {
+                let region = required(rest, "region")?.to_string();
+                let (x, y) = (number(rest, "x")?, number(rest, "y")?);
+                let r = number(rest, "radius")?;
+                let (_, color) = flags(rest)?;
+                Request::Circle {
+                    region,
+                    x,
+                    y,
+                    r,
+                    color,
+                }
+            }
+            "image" => {
+                let region = required(rest, "region")?.to_string();
+                let (x, y) = (number(rest, "x")?, number(rest, "y")?);
+                Request::Image {
+                    region,
+                    x,
+                    y,
+                    data: payload(rest)?,
+                }
+            }
+            "frame" => Request::Frame(payload(rest)?),
+            "contrast" => Request::Contrast(number(rest, "contrast")?),
+            "power" => Request::Power(switch(required(rest, "on or off")?)?),
+            "invert" => Request::Invert(switch(required(rest, "on or off")?)?),
+            "flush" => Request::Flush,
+            _ => return Err(format!("unknown command {}", command)),
+        };
+        request.check()?;
+        Ok(request)
+    }
+
+    // 尺寸和半径不能超过屏幕；坐标可以任意，超出的部分被裁掉
+    fn check(&self) -> Result<(), String> {
+        match *self {
+            Request::Rect { w, h, .. } if w > WIDTH as i32 || h > HEIGHT as i32 => {
+                Err(format!("rectangle larger than {}x{}", WIDTH, HEIGHT))
+            }
+            Request::Circle { r, .. } if r > WIDTH as i32 => {
+                Err(format!("radius larger than {}", WIDTH))
+            }
+            _ => Ok(()),
+        }
+    }
+
+    /// 请求行之后需要读取的数据
+    pub fn payload_mut(&mut self) -> Option<&mut Vec<u8>> {
+        match self {
+            Request::Image { data, .. } | Request::Frame(data) => Some(data),
+            _ => None,
+        }
+    }
+
+    // 检查请求并解码 image / frame 的数据，在加锁之前调用，坏数据不会占用屏幕
+    fn decode(&self) -> Result<Option<Image>, String> {
+        self.check()?;
+        let image = match self {
+            Request::Image { data, .. } | Request::Frame(data) => {
+                Image::decode(data).map_err(|e| e.to_string())?
+            }
+            _ => return Ok(None),
+        };
+        if matches!(self, Request::Frame(_)) && (image.width() != WIDTH || image.height() != HEIGHT)
+        {
+            return Err(format!("frame must be {}x{}", WIDTH, HEIGHT));
+        }
+        Ok(Some(image))
+    }
+}
+
+/// 执行一条请求：图片数据先在锁外解码，再加锁绘制。
+/// 套接字、HTTP 和 MQTT 的请求都经过这里
+pub fn execute(screen: &Mutex<Screen>, request: Request) -> Result<(), String> {
+    let image = request.decode()?;
+    screen
+        .lock()
+        .map_err(|_| "display unavailable".to_string())?
+        .apply(request, image)
+}
+
+/// 守护进程持有的屏幕状态，绘制通过 `region::Layout` 裁剪到区域内并记录改变的部分
+pub struct Screen {
+    layout: Layout,
+}
+
+impl Screen {
+    pub fn new(display: Display) -> Self {
+        Screen {
+            layout: Layout::new(display).region("screen", Rect::screen()),
+        }
+    }
+
+    /// 定义区域，超出屏幕的部分被截掉
+    pub fn define(&mut self, name: &str, rect: Rect) {
+        self.layout.define(name, rect);
+    }
+
+    pub fn region(&self, name: &str) -> Option<Rect> {
+        self.layout.bounds(name)
+    }
+
+    /// 在区域内绘制，区域不存在时返回错误
+    fn draw(&mut self, name: &str, f: impl FnOnce(&mut Canvas)) -> Result<(), String> {
+        self.layout.draw(name, f).map_err(|e| e.to_string())
+    }
+
+    /// 执行一条请求，失败时返回发给客户端的原因
+    pub fn execute(&mut self, request: Request) -> Result<(), String> {
+        let image = request.decode()?;
+        self.apply(request, image)
+    }
+
+    // `image` 是 `Request::decode` 解码好的图片
+    fn apply(&mut self, request: Request, image: Option<Image>) -> Result<(), String> {
+        match request {
+            Request::Region(name, rect) => self.define(&name, rect),
+            Request::Clear(None) => self.layout.clear(),
+            Request::Clear(Some(name)) => self.draw(&name, |c| c.clear())?,
+            Request::Text {
+                region,
+                font,
+                align,
+                valign,
+                text,
+            } => {
+                let font = self
+                    .layout
+                    .display_mut()
+                    .fonts()
+                    .get(&font)
+                    .ok_or_else(|| format!("unknown font {}", font))?;
+                self.draw(&region, |c| {
+                    c.clear();
+                    c.text_aligned(font.as_ref(), &text, align, valign);
+                })?;
+            }
+            Request::Pixel {
+                region,
+                x,
+                y,
+                color,
+            } => self.draw(&region, |c| c.pixel(x, y, color))?,
+            Request::Line {
+                region,
+                from,
+                to,
+                color,
+            } => self.draw(&region, |c| c.line(from.0, from.1, to.0, to.1, color))?,
+            Request::Rect {
+                region,
+                x,
+                y,
+                w,
+                h,
+                fill,
+                color,
+            } => self.draw(&region, |c| {
+                if fill {
+                    c.fill_rect(x, y, w, h, color);
+                } else {
+                    c.rect(x, y, w, h, color);
+                }
+            })?,
+            Request::Circle {
+                region,
+                x,
+                y,
+                r,
+                color,
+            } => self.draw(&region, |c| c.circle(x, y, r, color))?,
+            Request::Image { region, x, y, .. } => {
+                let image = image.ok_or("missing image data")?;
+                self.draw(&region, |c| c.image(x, y, &image))?;
+            }
+            Request::Frame(_) => {
+                let image = image.ok_or("missing frame data")?;
+                self.layout.draw_in(Rect::screen(), |c| {
+                    c.clear();
+                    c.image(0, 0, &image);
+                });
+            }
+            // 控制命令直接发送，不影响帧缓冲
+            Request::Contrast(contrast) => self.layout.display_mut().set_contrast(contrast),
+            Request::Power(on) => self.layout.display_mut().set_power(on),
+            Request::Invert(on) => self.layout.display_mut().set_color_mode(if on {
+                OLEDColorMode::ColorReserved
+            } else {
+                OLEDColorMode::ColorNormal
+            }),
+            Request::Flush => self.flush(),
+        }
+        Ok(())
+    }
+
+    /// 把改变过的部分发送到屏幕
+    pub fn flush(&mut self) {
+        self.layout.flush();
+    }
+
+    /// 对比度、显示开关、反色和区域列表，HTTP 和 MQTT 接口用它报告状态
+    #[cfg(any(feature = "http", feature = "mqtt"))]
+    pub fn state(&self) -> serde_json::Value {
+        let display = self.layout.display();
+        let regions: serde_json::Map<String, serde_json::Value> = self
+            .layout
+            .names()
+            .filter_map(|name| {
+                let r = self.layout.bounds(name)?;
+                Some((name.to_string(), serde_json::json!([r.x, r.y, r.w, r.h])))
+            })
+            .collect();
+        serde_json::json!({
+            "contrast": display.contrast(),
+            "power": display.is_on(),
+            "inverted": display.is_inverted(),
+            "simulated": display.is_simulated(),
+            "regions": regions,
+        })
+    }
+
+    pub fn layout(&self) -> &Layout {
+        &self.layout
+    }
+
+    pub fn layout_mut(&mut self) -> &mut Layout {
+        &mut self.layout
+    }
+}
+
+/// 守护进程：每个客户端连接一个线程，请求逐条加锁执行，保证不会交错；
+/// 刷新线程按间隔把帧缓冲发送到屏幕
+pub struct Server {
+    screen: Arc<Mutex<Screen>>,
+    interval: Duration,
+    started: Once,
+}
+
+impl Server {
+    /// 启动时初始化并清空屏幕，默认每100毫秒最多刷新一次
+    pub fn new(mut display: Display) -> Self {
+        display.init();
+        display.clear();
+        Server {
+            screen: Arc::new(Mutex::new(Screen::new(display))),
+            interval: Duration::from_millis(100),
+            started: Once::new(),
+        }
+    }
+
+    /// 两次刷新屏幕的最短间隔
+    pub fn refresh_interval(mut self, interval: Duration) -> Self {
+        self.interval = interval;
+        self
+    }
+
+    /// 预先定义区域
+    pub fn region(self, name: &str, rect: Rect) -> Self {
+        if let Ok(mut screen) = self.screen.lock() {
+            screen.define(name, rect);
+        }
+        self
+    }
+
+    pub fn screen(&self) -> Arc<Mutex<Screen>> {
+        self.screen.clone()
+    }
+
+    /// 启动刷新线程，多次调用只启动一个；`serve` 会自动调用
+    pub fn start_refresh(&self) {
+        self.started.call_once(|| {
+            let screen = self.screen.clone();
+            let interval = self.interval;
+            thread::spawn(move || {
+                loop {
+                    thread::sleep(interval);
+                    if let Ok(mut screen) = screen.lock() {
+                        screen.flush();
+                    }
+                }
+            });
+        });
+    }
+
+    /// 在监听的套接字上接受连接，直到出错才返回
+    pub fn serve(&self, listener: UnixListener) -> io::Result<()> {
+        self.start_refresh();
+        for stream in listener.incoming() {
+            let stream = stream?;
+            let screen = self.screen.clone();
+            thread::spawn(move || {
+                // 客户端断开或发送了无法继续解析的数据
+                let _ = handle(stream, &screen);
+            });
+        }
+        Ok(())
+    }
+}
+
+fn handle(stream: UnixStream, screen: &Mutex<Screen>) -> io::Result<()> {
+    let mut writer = stream.try_clone()?;
+    process(&mut BufReader::new(stream), &mut writer, screen)
+}
+
+/// 逐条执行请求直到读完，每条请求向 `writer` 写一行回复。
+// `image` / `frame` 请求行中的数据长度：不是这两个命令时为 None，长度无法解析时为 Some(None)
+fn payload_len(line: &str) -> Option<Option<u64>> {
+    let mut tokens = line.split_whitespace();
+    let index = match tokens.next()? {
+        "image" => 3,
+        "frame" => 0,
+        _ => return None,
+    };
+    Some(tokens.nth(index).and_then(|len| len.parse().ok()))
+}
+
+/// 套接字连接和 MQTT 的命令消息都用它处理
+pub fn process(
+    reader: &mut impl BufRead,
+    writer: &mut impl Write,
+    screen: &Mutex<Screen>,
+) -> io::Result<()> {
+    let mut line = Vec::new();
+    loop {
+        line.clear();
+        let n = Read::take(&mut *reader, MAX_LINE as u64).read_until(b'\n', &mut line)?;
+        if n == 0 {
+            return Ok(());
+        }
+        // 过长的行回复错误，丢弃到行尾后继续
+        if line.len() == MAX_LINE && !line.ends_with(b"\n") {
+            reader.skip_until(b'\n')?;
+            writeln!(writer, "err line too long")?;
+            continue;
+        }
+        let Ok(line) = std::str::from_utf8(&line) else {
+            writeln!(writer, "err invalid UTF-8")?;
+            continue;
+        };
+        if line.trim().is_empty() {
+            continue;
+        }
+        // image / frame 的数据长度先于其它参数取出：请求有错时按长度跳过数据，
+        // 不让数据被当成请求行；长度本身不可信时断开连接
+        let len = match payload_len(line) {
+            Some(Some(len)) => Some(len),
+            Some(None) => {
+                writeln!(writer, "err bad payload length, closing")?;
+                return Ok(());
+            }
+            None => None,
+        };
+        let result = match Request::parse(line) {
+            Ok(mut request) => {
+                if let Some(data) = request.payload_mut() {
+                    reader.read_exact(data)?;
+                }
+                execute(screen, request)
+            }
+            Err(e) => {
+                if let Some(len) = len {
+                    io::copy(&mut Read::take(&mut *reader, len), &mut io::sink())?;
+                }
+                Err(e)
+            }
+        };
+        match result {
+            Ok(()) => writeln!(writer, "ok")?,
+            Err(e) => writeln!(writer, "err {}", e)?,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::frame_lock;
+
+    fn screen() -> Screen {
+        Screen::new(Display::simulated())
+    }
+
+    fn lit(image: &Image) -> usize {
+        (0..image.height())
+            .flat_map(|y| (0..image.width()).map(move |x| (x, y)))
+            .filter(|&(x, y)| image.pixel(x, y))
+            .count()
+    }
+
+    #[test]
+    fn parse_requests() {
+        assert_eq!(
+            Request::parse("text status align=right a\\nb\n"),
+            Ok(Request::Text {
+                region: "status".to_string(),
+                font: "8x8".to_string(),
+                align: HAlign::Right,
+                valign: VAlign::Top,
+                text: "a\nb".to_string(),
+            })
+        );
+        assert_eq!(
+            Request::parse("rect screen 1 2 3 4 fill off"),
+            Ok(Request::Rect {
+                region: "screen".to_string(),
+                x: 1,
+                y: 2,
+                w: 3,
+                h: 4,
+                fill: true,
+                color: false,
+            })
+        );
+        assert!(Request::parse("text screen align=middle x").is_err());
+        assert!(Request::parse("frame 99999999").is_err());
+        assert!(Request::parse("bogus").is_err());
+        assert!(Request::parse("rect screen 0 0 129 1").is_err());
+        assert!(Request::parse("circle screen 0 0 2000000000").is_err());
+    }
+
+    #[test]
+    fn draws_into_the_frame_buffer() {
+        let _lock = frame_lock();
+        let mut screen = screen();
+        screen
+            .execute(Request::parse("rect screen 0 0 128 64").unwrap())
+            .unwrap();
+        let frame = Image::from_frame();
+        assert!(frame.pixel(0, 0) && frame.pixel(127, 63) && !frame.pixel(1, 1));
+        assert_eq!(lit(&frame), 2 * 128 + 2 * 62);
+
+        screen.execute(Request::Clear(None)).unwrap();
+        assert_eq!(lit(&Image::from_frame()), 0);
+    }
+
+    #[test]
+    fn clips_to_regions() {
+        let _lock = frame_lock();
+        let mut screen = screen();
+        screen
+            .execute(Request::parse("region box 10 10 4 4").unwrap())
+            .unwrap();
+        screen
+            .execute(Request::parse("line box -100 1 100 1").unwrap())
+            .unwrap();
+        let frame = Image::from_frame();
+        assert_eq!(lit(&frame), 4);
+        assert!((10..14).all(|x| frame.pixel(x, 11)));
+        assert!(
+            screen
+                .execute(Request::parse("pixel nope 0 0").unwrap())
+                .is_err()
+        );
+    }
+
+    #[test]
+    fn far_coordinates_are_clipped() {
+        let _lock = frame_lock();
+        let mut screen = screen();
+        for line in [
+            "line screen 0 0 2000000000 0",
+            "line screen -2147483648 -2147483648 2147483647 2147483647",
+            "rect screen 2147483647 2147483647 128 64",
+            "circle screen -2147483648 0 128",
+            "pixel screen 2147483647 -2147483648",
+        ] {
+            screen.execute(Request::parse(line).unwrap()).unwrap();
+        }
+        let frame = Image::from_frame();
+        assert!((0..WIDTH).all(|x| frame.pixel(x, 0)));
+        assert!(frame.pixel(0, 0) && frame.pixel(63, 63) && frame.pixel(10, 10));
+        assert!(
+            screen
+                .execute(Request::Circle {
+                    region: "screen".to_string(),
+                    x: 0,
+                    y: 0,
+                    r: i32::MAX,
+                    color: true,
+                })
+                .is_err()
+        );
+    }
+
+    #[test]
+    fn frame_replaces_the_screen() {
+        let _lock = frame_lock();
+        let mut screen = screen();
+        let mut image = Image::new(WIDTH, HEIGHT);
+        image.set(5, 6, true);
+        image.set(127, 63, true);
+        screen.execute(Request::Frame(image.to_pbm())).unwrap();
+        assert_eq!(Image::from_frame(), image);
+
+        let small = Image::new(8, 8).to_pbm();
+        assert!(screen.execute(Request::Frame(small)).is_err());
+        assert!(
+            screen
+                .execute(Request::Frame(b"P4\n1 1\n".to_vec()))
+                .is_err()
+        );
+        assert_eq!(Image::from_frame(), image);
+    }
+
+    #[cfg(feature = "png")]
+    #[test]
+    fn png_round_trip() {
+        let _lock = frame_lock();
+        let mut screen = screen();
+        screen
+            .execute(Request::parse("circle screen 64 32 20").unwrap())
+            .unwrap();
+        let frame = Image::from_frame();
+        assert_eq!(Image::from_png(&frame.to_png()), Ok(frame.clone()));
+
+        screen.execute(Request::Clear(None)).unwrap();
+        screen.execute(Request::Frame(frame.to_png())).unwrap();
+        assert_eq!(Image::from_frame(), frame);
+    }
+
+    #[test]
+    fn process_replies_per_line() {
+        let _lock = frame_lock();
+        let screen = Mutex::new(screen());
+        let image = Image::new(2, 2).to_pbm();
+        let mut input = format!(
+            "pixel screen 0 0\n\nbogus\nimage screen 0 0 {}\n",
+            image.len()
+        )
+        .into_bytes();
+        input.extend_from_slice(&image);
+        input.extend_from_slice(b"flush\n");
+        let mut output = Vec::new();
+        process(&mut &input[..], &mut output, &screen).unwrap();
+        assert_eq!(
+            String::from_utf8(output).unwrap(),
+            "ok\nerr unknown command bogus\nok\nok\n"
+        );
+    }
+
+    #[test]
+    fn bad_payload_headers_skip_the_payload() {
+        let _lock = frame_lock();
+        let screen = Mutex::new(screen());
+        let data = b"pixel screen 0 0\n";
+        let mut input = format!("image screen abc 0 {}\n", data.len()).into_bytes();
+        input.extend_from_slice(data);
+        input.extend_from_slice(format!("image nope 0 0 {}\n", data.len()).as_bytes());
+        input.extend_from_slice(data);
+        input.extend_from_slice(b"flush\n");
+        input.extend_from_slice(format!("image screen 0 0 {}\n", MAX_PAYLOAD + 1).as_bytes());
+        input.extend_from_slice(data);
+        let mut output = Vec::new();
+        process(&mut &input[..], &mut output, &screen).unwrap();
+        assert_eq!(
+            String::from_utf8(output).unwrap(),
+            format!(
+                "err bad x: abc\nerr bad image: not a PBM (P1/P4) file\nok\nerr payload too large: {}\n",
+                MAX_PAYLOAD + 1
+            )
+        );
+        assert!(!Image::from_frame().pixel(0, 0));
+    }
+
+    #[test]
+    fn unreadable_payload_length_closes() {
+        let _lock = frame_lock();
+        let screen = Mutex::new(screen());
+        let mut output = Vec::new();
+        let input = b"frame many\npixel screen 0 0\n";
+        process(&mut &input[..], &mut output, &screen).unwrap();
+        assert_eq!(output, b"err bad payload length, closing\n");
+        assert!(!Image::from_frame().pixel(0, 0));
+    }
+
+    #[test]
+    fn images_are_clipped_like_shapes() {
+        let _lock = frame_lock();
+        let mut screen = screen();
+        let mut image = Image::new(3, 2);
+        image.set(1, 1, true);
+        image.set(2, 1, true);
+        let data = image.to_pbm();
+        for (x, y) in [(-1, -1), (-100, 0), (126, 62), (1000, -1000)] {
+            let request = Request::Image {
+                region: "screen".to_string(),
+                x,
+                y,
+                data: data.clone(),
+            };
+            assert_eq!(screen.execute(request), Ok(()));
+        }
+        let frame = Image::from_frame();
+        assert!(frame.pixel(0, 0) && frame.pixel(1, 0));
+        assert!(frame.pixel(127, 63));
+        assert_eq!(lit(&frame), 3);
+    }
+
+    #[test]
+    fn process_rejects_long_lines() {
+        let _lock = frame_lock();
+        let screen = Mutex::new(screen());
+        let mut input = format!("text screen {}\n", "x".repeat(MAX_LINE)).into_bytes();
+        input.extend_from_slice(b"\xff\nflush\n");
+        input.extend(std::iter::repeat_n(b'y', MAX_LINE * 3));
+        let mut output = Vec::new();
+        process(&mut &input[..], &mut output, &screen).unwrap();
+        assert_eq!(
+            String::from_utf8(output).unwrap(),
+            "err line too long\nerr invalid UTF-8\nok\nerr line too long\n"
+        );
+    }
+}
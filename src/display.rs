@@ -0,0 +1,215 @@
+use crate::font::{FontFace, FontRegistry, MissingGlyphs};
+use crate::text::{
+    self, HAlign, MarkupError, Rect, TextBox, TextDirection, TextLayout, TextStyle, VAlign,
+};
+use crate::{Buffer, OLEDColorMode, ScrollDirection};
+use linux_embedded_hal::I2cdev;
+use linux_embedded_hal::i2cdev::linux::LinuxI2CError;
+use std::ops::Range;
+
+/// 持有I2C总线和字体表的显示屏对象，封装了模块级的绘制函数
+pub struct Display {
+    i2c: Option<I2cdev>, // 模拟屏幕没有总线
+    fonts: FontRegistry,
+    contrast: u8,
+    power: bool,
+    inverted: bool,
+}
+
+impl Display {
+    pub fn new(i2c: I2cdev) -> Self {
+        Display {
+            i2c: Some(i2c),
+            ..Display::simulated()
+        }
+    }
+
+    /// 不连接硬件的模拟屏幕：绘制只修改帧缓冲，可以用 `frame` 或 `Image::from_frame` 读出，
+    /// 用于在开发机上测试。
+    ///
+    /// 帧缓冲是整个进程共用的，模拟屏幕也没有自己的缓冲区：同时使用多个 `Display`
+    /// 时（包括并行运行的测试）需要自己保证绘制不会交错，例如都通过同一个 `daemon::Screen`
+    pub fn simulated() -> Self {
+        Display {
+            i2c: None,
+            fonts: FontRegistry::with_builtin(),
+            contrast: 0xDF,
+            power: true,
+            inverted: false,
+        }
+    }
+
+    /// 打开I2C设备，例如 `/dev/i2c-1`
+    pub fn open(path: &str) -> Result<Self, LinuxI2CError> {
+        Ok(Display::new(I2cdev::new(path)?))
+    }
+
+    pub fn is_simulated(&self) -> bool {
+        self.i2c.is_none()
+    }
+
+    /// 初始化后对比度、显示开关和反色恢复为默认值
+    pub fn init(&mut self) {
+        self.contrast = 0xDF;
+        self.power = true;
+        self.inverted = false;
+        if let Some(i2c) = &mut self.i2c {
+            crate::init(i2c);
+        }
+    }
+
+    pub fn clear(&mut self) {
+        if let Some(i2c) = &mut self.i2c {
+            crate::clear(i2c);
+        }
+    }
+
+    pub fn newframe(&mut self) {
+        crate::newframe();
+    }
+
+    pub fn showframe(&mut self) {
+        if let Some(i2c) = &mut self.i2c {
+            crate::showframe(i2c);
+        }
+    }
+
+    /// 只发送一页中的部分列
+    pub fn showframe_span(&mut self, page: usize, columns: Range<usize>) {
+        if let Some(i2c) = &mut self.i2c {
+            crate::showframe_span(i2c, page, columns);
+        }
+    }
+
+    /// 用指定字体在帧缓冲中绘制文字，返回字体不支持的字符
+    pub fn text(
+        &mut self,
+        x: u8,
+        y: u8,
+        font: &dyn FontFace,
+        s: &str,
+    ) -> Result<(), MissingGlyphs> {
+        crate::print_string(x, y, font, s)
+    }
+
+    /// 按样式绘制文字：加粗、下划线、删除线、斜体、反色
+    pub fn text_styled(
+        &mut self,
+        x: u8,
+        y: u8,
+        font: &dyn FontFace,
+        s: &str,
+        style: TextStyle,
+    ) -> Result<(), MissingGlyphs> {
+        crate::print_string_styled(x, y, font, s, style)
+    }
+
+    /// 按方向绘制文字（旋转90/270度或竖排），返回外框尺寸
+    pub fn text_directed(
+        &mut self,
+        x: u8,
+        y: u8,
+        font: &dyn FontFace,
+        s: &str,
+        dir: TextDirection,
+    ) -> (u16, u16) {
+        text::draw_text_directed(x, y, font, s, dir)
+    }
+
+    /// 绘制标记文本，`[font:..]` 从本对象的字体表中查找，见 `text::RichText`
+    pub fn markup(
+        &mut self,
+        x: u8,
+        y: u8,
+        font: &dyn FontFace,
+        s: &str,
+    ) -> Result<(u16, u16), MarkupError> {
+        text::draw_markup(x, y, &self.fonts, font, s)
+    }
+
+    /// 用字体表中的字体绘制文字，字体不存在时返回 `false`
+    pub fn text_named(&mut self, x: u8, y: u8, font: &str, s: &str) -> bool {
+        match self.fonts.get(font) {
+            Some(f) => {
+                let _ = crate::print_string(x, y, f.as_ref(), s);
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// 在矩形区域内对齐绘制文字
+    pub fn text_aligned(&mut self, rect: Rect, font: &dyn FontFace, s: &str, h: HAlign, v: VAlign) {
+        text::draw_text_aligned(rect, font, s, h, v);
+    }
+
+    /// 在矩形区域内按词换行绘制文字，返回排版结果用于分页
+    pub fn text_box(&mut self, rect: Rect, font: &dyn FontFace, s: &str) -> TextLayout {
+        TextBox::new(rect, font).ellipsis(true).draw(s)
+    }
+
+    pub fn display_buffer(&mut self, buffer: &Buffer, x: u8, y: u8, font: &dyn FontFace) {
+        crate::newframe();
+        crate::draw_buffer(buffer, x, y, font);
+        self.showframe();
+    }
+
+    pub fn set_color_mode(&mut self, mode: OLEDColorMode) {
+        self.inverted = matches!(mode, OLEDColorMode::ColorReserved);
+        if let Some(i2c) = &mut self.i2c {
+            crate::set_color_mode(i2c, mode);
+        }
+    }
+
+    pub fn set_contrast(&mut self, contrast: u8) {
+        self.contrast = contrast;
+        if let Some(i2c) = &mut self.i2c {
+            crate::set_contrast(i2c, contrast);
+        }
+    }
+
+    pub fn set_power(&mut self, on: bool) {
+        self.power = on;
+        if let Some(i2c) = &mut self.i2c {
+            crate::set_power(i2c, on);
+        }
+    }
+
+    /// 最后设置的对比度
+    pub fn contrast(&self) -> u8 {
+        self.contrast
+    }
+
+    pub fn is_on(&self) -> bool {
+        self.power
+    }
+
+    pub fn is_inverted(&self) -> bool {
+        self.inverted
+    }
+
+    pub fn start_scroll(&mut self, dir: ScrollDirection, start: u8, end: u8, interval: u8) {
+        if let Some(i2c) = &mut self.i2c {
+            crate::start_scroll(i2c, dir, start, end, interval);
+        }
+    }
+
+    pub fn stop_scroll(&mut self) {
+        if let Some(i2c) = &mut self.i2c {
+            crate::stop_scroll(i2c);
+        }
+    }
+
+    pub fn fonts(&self) -> &FontRegistry {
+        &self.fonts
+    }
+
+    pub fn fonts_mut(&mut self) -> &mut FontRegistry {
+        &mut self.fonts
+    }
+
+    /// I2C总线，模拟屏幕返回 `None`
+    pub fn i2c(&mut self) -> Option<&mut I2cdev> {
+        self.i2c.as_mut()
+    }
+}
@@ -0,0 +1,202 @@
+//! 帧缓冲上的基本图形，坐标可以为负或超出屏幕，超出的部分被裁掉
+
+use crate::image::Image;
+use crate::{HEIGHT, WIDTH, setpixel};
+
+/// 单个像素
+pub fn pixel(x: i32, y: i32, color: bool) {
+    if (0..WIDTH as i32).contains(&x) && (0..HEIGHT as i32).contains(&y) {
+        setpixel(x as u8, y as u8, color);
+    }
+}
+
+// Cohen–Sutherland 区域编码
+const LEFT: u8 = 1;
+const RIGHT: u8 = 2;
+const TOP: u8 = 4;
+const BOTTOM: u8 = 8;
+
+fn outcode(x: i64, y: i64) -> u8 {
+    let mut code = 0;
+    if x < 0 {
+        code |= LEFT;
+    } else if x >= WIDTH as i64 {
+        code |= RIGHT;
+    }
+    if y < 0 {
+        code |= TOP;
+    } else if y >= HEIGHT as i64 {
+        code |= BOTTOM;
+    }
+    code
+}
+
+// 把线段裁到屏幕内，完全在屏幕外时返回 None
+fn clip_line(x0: i32, y0: i32, x1: i32, y1: i32) -> Option<(i32, i32, i32, i32)> {
+    let (mut x0, mut y0, mut x1, mut y1) = (x0 as i64, y0 as i64, x1 as i64, y1 as i64);
+    let (right, bottom) = (WIDTH as i64 - 1, HEIGHT as i64 - 1);
+    let (mut c0, mut c1) = (outcode(x0, y0), outcode(x1, y1));
+    // 正常最多裁4次；取整误差让线段在角上来回时当作不可见
+    for _ in 0..8 {
+        if c0 | c1 == 0 {
+            return Some((x0 as i32, y0 as i32, x1 as i32, y1 as i32));
+        }
+        if c0 & c1 != 0 {
+            return None;
+        }
+        let code = if c0 != 0 { c0 } else { c1 };
+        // 与屏幕边界的交点，坐标差可达 2^32，乘积用 i128 计算
+        let at = |a0: i64, a1: i64, b0: i64, b1: i64, b: i64| {
+            (a0 as i128 + (a1 - a0) as i128 * (b - b0) as i128 / (b1 - b0) as i128) as i64
+        };
+        let (x, y) = if code & TOP != 0 {
+            (at(x0, x1, y0, y1, 0), 0)
+        } else if code & BOTTOM != 0 {
+            (at(x0, x1, y0, y1, bottom), bottom)
+        } else if code & LEFT != 0 {
+            (0, at(y0, y1, x0, x1, 0))
+        } else {
+            (right, at(y0, y1, x0, x1, right))
+        };
+        if code == c0 {
+            (x0, y0) = (x, y);
+            c0 = outcode(x0, y0);
+        } else {
+            (x1, y1) = (x, y);
+            c1 = outcode(x1, y1);
+        }
+    }
+    None
+}
+
+/// 从 `(x0, y0)` 到 `(x1, y1)` 的直线，包含两个端点。先裁到屏幕内再逐点绘制，
+/// 端点离屏幕很远也不会多花时间
+pub fn line(x0: i32, y0: i32, x1: i32, y1: i32, color: bool) {
+    let Some((x0, y0, x1, y1)) = clip_line(x0, y0, x1, y1) else {
+        return;
+    };
+    // Bresenham 算法
+    let dx = (x1 - x0).abs();
+    let dy = -(y1 - y0).abs();
+    let sx = if x0 < x1 { 1 } else { -1 };
+    let sy = if y0 < y1 { 1 } else { -1 };
+    let (mut x, mut y) = (x0, y0);
+    let mut err = dx + dy;
+    loop {
+        pixel(x, y, color);
+        if x == x1 && y == y1 {
+            break;
+        }
+        let e2 = 2 * err;
+        if e2 >= dy {
+            err += dy;
+            x += sx;
+        }
+        if e2 <= dx {
+            err += dx;
+            y += sy;
+        }
+    }
+}
+
+/// 矩形边框
+pub fn rect(x: i32, y: i32, w: i32, h: i32, color: bool) {
+    if w <= 0 || h <= 0 {
+        return;
+    }
+    let (x1, y1) = (x.saturating_add(w - 1), y.saturating_add(h - 1));
+    line(x, y, x1, y, color);
+    line(x, y1, x1, y1, color);
+    line(x, y, x, y1, color);
+    line(x1, y, x1, y1, color);
+}
+
+/// 填充矩形
+pub fn fill_rect(x: i32, y: i32, w: i32, h: i32, color: bool) {
+    for py in y.max(0)..y.saturating_add(h).min(HEIGHT as i32) {
+        for px in x.max(0)..x.saturating_add(w).min(WIDTH as i32) {
+            setpixel(px as u8, py as u8, color);
+        }
+    }
+}
+
+/// 以 `(cx, cy)` 为圆心、`r` 为半径的圆。计算量与半径成正比，
+/// 整个圆都在屏幕外或屏幕完全在圆内时直接返回
+pub fn circle(cx: i32, cy: i32, r: i32, color: bool) {
+    if r < 0 {
+        return;
+    }
+    let (cx64, cy64, r64) = (cx as i64, cy as i64, r as i64);
+    let (right, bottom) = (WIDTH as i64 - 1, HEIGHT as i64 - 1);
+    if cx64 + r64 < 0 || cx64 - r64 > right || cy64 + r64 < 0 || cy64 - r64 > bottom {
+        return;
+    }
+    let inside = |x: i64, y: i64| {
+        ((x - cx64) as i128).pow(2) + ((y - cy64) as i128).pow(2) < ((r64 - 1) as i128).pow(2)
+    };
+    if r > 1
+        && [(0, 0), (right, 0), (0, bottom), (right, bottom)]
+            .iter()
+            .all(|&(x, y)| inside(x, y))
+    {
+        return;
+    }
+    // 中点画圆法，每次画出八个对称点
+    let (mut x, mut y) = (r, 0);
+    let mut err = 1 - r64;
+    while x >= y {
+        for (px, py) in [(x, y), (y, x), (-y, x), (-x, y)] {
+            pixel(cx.saturating_add(px), cy.saturating_add(py), color);
+            pixel(cx.saturating_sub(px), cy.saturating_sub(py), color);
+        }
+        y += 1;
+        if err < 0 {
+            err += 2 * y as i64 + 1;
+        } else {
+            x -= 1;
+            err += 2 * (y as i64 - x as i64) + 1;
+        }
+    }
+}
+
+/// 左上角在 `(x, y)` 的图片，只遍历屏幕内的部分
+pub fn image(x: i32, y: i32, image: &Image) {
+    // 图片中落在屏幕内的行列范围
+    let visible = |at: i32, len: usize, max: usize| {
+        let at = at as i64;
+        let start = (-at).clamp(0, len as i64) as usize;
+        let end = (max as i64 - at).clamp(0, len as i64) as usize;
+        start..end.max(start)
+    };
+    for row in visible(y, image.height(), HEIGHT) {
+        for col in visible(x, image.width(), WIDTH) {
+            let (px, py) = (x as i64 + col as i64, y as i64 + row as i64);
+            setpixel(px as u8, py as u8, image.pixel(col, row));
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn clip_keeps_visible_lines() {
+        assert_eq!(clip_line(3, 4, 100, 50), Some((3, 4, 100, 50)));
+        assert_eq!(clip_line(-10, 5, 200, 5), Some((0, 5, 127, 5)));
+        assert_eq!(clip_line(0, -64, 0, 1000), Some((0, 0, 0, 63)));
+        assert_eq!(clip_line(-64, -64, 191, 191), Some((0, 0, 63, 63)));
+        assert_eq!(
+            clip_line(i32::MIN, i32::MIN, i32::MAX, i32::MAX),
+            Some((0, 0, 63, 63))
+        );
+    }
+
+    #[test]
+    fn clip_drops_hidden_lines() {
+        assert_eq!(clip_line(-5, -5, 200, -1), None);
+        assert_eq!(clip_line(128, 0, 128, 63), None);
+        assert_eq!(clip_line(-100, 10, 10, -100), None);
+        assert_eq!(clip_line(i32::MIN, 0, i32::MIN, i32::MAX), None);
+    }
+}
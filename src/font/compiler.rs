@@ -0,0 +1,556 @@
+//! 字体编译器：从 BDF / HZK 点阵字库中抽取指定字符，生成与 font_bytes.rs
+//! 相同格式的 Rust 模块。既可以在 build.rs 中调用，也可以通过 `fontc` 命令行使用。
+
+use super::GlyphMetrics;
+use std::collections::BTreeSet;
+use std::fmt::{self, Write};
+
+/// 编译过程中的错误
+#[derive(Debug)]
+pub enum CompileError {
+    /// BDF 文件格式错误，附带行号
+    Bdf(usize, String),
+    /// 目标尺寸不合法（高度必须是8的倍数）
+    Size(String),
+    /// 字库中缺少的字符
+    Missing(Vec<char>),
+}
+
+impl fmt::Display for CompileError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            CompileError::Bdf(line, msg) => write!(f, "BDF line {}: {}", line, msg),
+            CompileError::Size(msg) => write!(f, "invalid size: {}", msg),
+            CompileError::Missing(chars) => {
+                write!(f, "missing glyphs:")?;
+                for c in chars {
+                    write!(f, " U+{:04X}({})", *c as u32, c)?;
+                }
+                Ok(())
+            }
+        }
+    }
+}
+
+impl std::error::Error for CompileError {}
+
+/// 单色位图，按行存储，`true` 为点亮
+#[derive(Clone)]
+pub struct Bitmap {
+    pub w: usize,
+    pub h: usize,
+    pixels: Vec<bool>,
+}
+
+impl Bitmap {
+    pub fn new(w: usize, h: usize) -> Self {
+        Bitmap {
+            w,
+            h,
+            pixels: vec![false; w * h],
+        }
+    }
+
+    pub fn get(&self, x: usize, y: usize) -> bool {
+        x < self.w && y < self.h && self.pixels[y * self.w + x]
+    }
+
+    pub fn set(&mut self, x: usize, y: usize, on: bool) {
+        if x < self.w && y < self.h {
+            self.pixels[y * self.w + x] = on;
+        }
+    }
+
+    /// 转换为 OLED 的页格式：逐页输出，每页 `w` 个字节，字节的低位在上
+    pub fn to_pages(&self) -> Vec<u8> {
+        let mut out = Vec::with_capacity(self.h.div_ceil(8) * self.w);
+        for page in 0..self.h.div_ceil(8) {
+            for x in 0..self.w {
+                let mut byte = 0u8;
+                for bit in 0..8 {
+                    if self.get(x, page * 8 + bit) {
+                        byte |= 1 << bit;
+                    }
+                }
+                out.push(byte);
+            }
+        }
+        out
+    }
+}
+
+/// 点阵字库来源
+pub trait GlyphSource {
+    /// 取出一个字符的点阵，已放置在 `w` x `h` 的字符格中
+    fn glyph(&self, ch: char, w: usize, h: usize) -> Option<Bitmap>;
+}
+
+struct BdfGlyph {
+    bbx: (usize, usize, i32, i32),
+    rows: Vec<Vec<u8>>,
+}
+
+/// BDF 字库（只解析绘制需要的字段）
+pub struct BdfFont {
+    ascent: i32,
+    glyphs: std::collections::HashMap<char, BdfGlyph>,
+}
+
+impl BdfFont {
+    pub fn parse(text: &str) -> Result<Self, CompileError> {
+        let mut ascent = None;
+        let mut bbox_h = 0;
+        let mut bbox_y = 0;
+        let mut glyphs = std::collections::HashMap::new();
+
+        let mut encoding: Option<u32> = None;
+        let mut bbx = (0, 0, 0, 0);
+        let mut bitmap: Option<Vec<Vec<u8>>> = None;
+
+        for (n, line) in text.lines().enumerate() {
+            let lineno = n + 1;
+            let mut parts = line.split_whitespace();
+            let Some(key) = parts.next() else { continue };
+            let nums = |parts: std::str::SplitWhitespace, count: usize| {
+                let v: Vec<i32> = parts.filter_map(|p| p.parse().ok()).collect();
+                if v.len() < count {
+                    Err(CompileError::Bdf(
+                        lineno,
+                        format!("expected {} numbers after {}", count, key),
+                    ))
+                } else {
+                    Ok(v)
+                }
+            };
+
+            if let Some(rows) = bitmap.as_mut() {
+                if key == "ENDCHAR" {
+                    let rows = bitmap.take().unwrap();
+                    if let Some(ch) = encoding.take().and_then(char::from_u32) {
+                        glyphs.insert(ch, BdfGlyph { bbx, rows });
+                    }
+                } else {
+                    let bytes = (0..key.len() / 2)
+                        .map(|i| u8::from_str_radix(&key[i * 2..i * 2 + 2], 16))
+                        .collect::<Result<Vec<u8>, _>>()
+                        .map_err(|_| {
+                            CompileError::Bdf(lineno, format!("bad bitmap row {}", key))
+                        })?;
+                    rows.push(bytes);
+                }
+                continue;
+            }
+
+            match key {
+                "FONTBOUNDINGBOX" => {
+                    let v = nums(parts, 4)?;
+                    bbox_h = v[1];
+                    bbox_y = v[3];
+                }
+                "FONT_ASCENT" => ascent = Some(nums(parts, 1)?[0]),
+                "STARTCHAR" => {
+                    encoding = None;
+                    bbx = (0, 0, 0, 0);
+                }
+                "ENCODING" => {
+                    let v = nums(parts, 1)?;
+                    encoding = u32::try_from(v[0]).ok();
+                }
+                "BBX" => {
+                    let v = nums(parts, 4)?;
+                    bbx = (v[0].max(0) as usize, v[1].max(0) as usize, v[2], v[3]);
+                }
+                "BITMAP" => bitmap = Some(Vec::new()),
+                _ => {}
+            }
+        }
+
+        Ok(BdfFont {
+            // 没有 FONT_ASCENT 时用字体包围盒推算基线
+            ascent: ascent.unwrap_or(bbox_h + bbox_y),
+            glyphs,
+        })
+    }
+}
+
+impl GlyphSource for BdfFont {
+    fn glyph(&self, ch: char, w: usize, h: usize) -> Option<Bitmap> {
+        let g = self.glyphs.get(&ch)?;
+        let (bw, bh, bx, by) = g.bbx;
+        let mut bitmap = Bitmap::new(w, h);
+        // 字形顶部相对字符格顶部的位置：基线在 ascent 处
+        let top = self.ascent - (by + bh as i32);
+        for (row, bytes) in g.rows.iter().enumerate().take(bh) {
+            for col in 0..bw {
+                let on = bytes
+                    .get(col / 8)
+                    .is_some_and(|b| b & (0x80 >> (col % 8)) != 0);
+                let x = bx + col as i32;
+                let y = top + row as i32;
+                if on && x >= 0 && y >= 0 {
+                    bitmap.set(x as usize, y as usize, true);
+                }
+            }
+        }
+        Some(bitmap)
+    }
+}
+
+/// HZK 系列 GB2312 点阵字库（HZK12/HZK14/HZK16 等横向取模格式）
+pub struct HzkFont {
+    data: Vec<u8>,
+    size: usize,
+}
+
+impl HzkFont {
+    /// `size` 为字库的点阵边长，例如 HZK16 为 16
+    pub fn new(data: Vec<u8>, size: usize) -> Self {
+        HzkFont { data, size }
+    }
+
+    fn gb2312(ch: char) -> Option<(u8, u8)> {
+        let mut buf = [0u8; 4];
+        let (bytes, _, unmappable) = encoding_rs::GBK.encode(ch.encode_utf8(&mut buf));
+        match bytes.as_ref() {
+            [qu @ 0xA1..=0xF7, wei @ 0xA1..=0xFE] if !unmappable => Some((*qu, *wei)),
+            _ => None,
+        }
+    }
+}
+
+impl GlyphSource for HzkFont {
+    fn glyph(&self, ch: char, w: usize, h: usize) -> Option<Bitmap> {
+        let (qu, wei) = Self::gb2312(ch)?;
+        let row_bytes = self.size.div_ceil(8);
+        let len = row_bytes * self.size;
+        let offset = ((qu - 0xA1) as usize * 94 + (wei - 0xA1) as usize) * len;
+        let data = self.data.get(offset..offset + len)?;
+        let mut bitmap = Bitmap::new(w, h);
+        for y in 0..self.size {
+            for x in 0..self.size {
+                if data[y * row_bytes + x / 8] & (0x80 >> (x % 8)) != 0 {
+                    bitmap.set(x, y, true);
+                }
+            }
+        }
+        Some(bitmap)
+    }
+}
+
+/// 生成选项
+pub struct Options {
+    /// 生成的常量名前缀，例如 `CJK16` 会生成 `CJK16_CHARS` / `CJK16_GLYPHS`
+    pub name: String,
+    /// 字符格宽度
+    pub w: usize,
+    /// 字符格高度，必须是8的倍数
+    pub h: usize,
+    /// 生成 `Font` 常量时使用的基础字体（提供ASCII部分），为空则不生成
+    pub base: Option<String>,
+    /// 引用本库时使用的路径，在库内部使用时为 `crate`
+    pub crate_path: String,
+    /// 生成比例字体度量表 `<name>_METRICS`
+    pub proportional: bool,
+}
+
+/// 从字符串中收集需要编译的字符：去重、排序，ASCII字符由内置字体提供因此跳过
+pub fn collect_chars<'a>(texts: impl IntoIterator<Item = &'a str>) -> Vec<char> {
+    let set: BTreeSet<char> = texts
+        .into_iter()
+        .flat_map(|s| s.chars())
+        .filter(|c| !c.is_ascii() && !c.is_control())
+        .collect();
+    set.into_iter().collect()
+}
+
+/// 提取 Rust 源码中的字符串字面量（支持普通字符串和原始字符串）
+pub fn string_literals(source: &str) -> Vec<String> {
+    let mut out = Vec::new();
+    let chars: Vec<char> = source.chars().collect();
+    let mut i = 0;
+    while i < chars.len() {
+        match chars[i] {
+            // 跳过注释，块注释可以嵌套
+            '/' if chars.get(i + 1) == Some(&'/') => {
+                while i < chars.len() && chars[i] != '\n' {
+                    i += 1;
+                }
+            }
+            '/' if chars.get(i + 1) == Some(&'*') => {
+                let mut depth = 0;
+                while i < chars.len() {
+                    match (chars[i], chars.get(i + 1)) {
+                        ('/', Some('*')) => {
+                            depth += 1;
+                            i += 2;
+                        }
+                        ('*', Some('/')) => {
+                            depth -= 1;
+                            i += 2;
+                            if depth == 0 {
+                                break;
+                            }
+                        }
+                        _ => i += 1,
+                    }
+                }
+            }
+            // 跳过字符字面量，避免 '"' 被当成字符串开头
+            '\'' if chars.get(i + 2) == Some(&'\'') => i += 3,
+            '\'' if chars.get(i + 1) == Some(&'\\') => {
+                i += 2;
+                while i < chars.len() && chars[i] != '\'' {
+                    i += 1;
+                }
+                i += 1;
+            }
+            'r' if matches!(chars.get(i + 1), Some('"') | Some('#'))
+                && (i == 0 || !chars[i - 1].is_alphanumeric()) =>
+            {
+                let mut j = i + 1;
+                let mut hashes = 0;
+                while chars.get(j) == Some(&'#') {
+                    hashes += 1;
+                    j += 1;
+                }
+                if chars.get(j) != Some(&'"') {
+                    i = j;
+                    continue;
+                }
+                j += 1;
+                let start = j;
+                while j < chars.len() {
+                    if chars[j] == '"' && (0..hashes).all(|k| chars.get(j + 1 + k) == Some(&'#')) {
+                        break;
+                    }
+                    j += 1;
+                }
+                out.push(chars[start..j.min(chars.len())].iter().collect());
+                i = j + 1 + hashes;
+            }
+            '"' => {
+                let mut s = String::new();
+                i += 1;
+                while i < chars.len() && chars[i] != '"' {
+                    if chars[i] == '\\' {
+                        i += 1;
+                        match chars.get(i) {
+                            Some('n') => s.push('\n'),
+                            Some('u') => {
+                                // \u{XXXX}，只在本字符串内找右括号，格式不对时原样保留
+                                let end = (chars.get(i + 1) == Some(&'{'))
+                                    .then(|| {
+                                        chars[i..]
+                                            .iter()
+                                            .take_while(|&&c| c != '"')
+                                            .position(|&c| c == '}')
+                                    })
+                                    .flatten();
+                                let c = end.and_then(|end| {
+                                    let hex: String = chars[i + 2..i + end].iter().collect();
+                                    u32::from_str_radix(&hex, 16).ok().and_then(char::from_u32)
+                                });
+                                match (c, end) {
+                                    (Some(c), Some(end)) => {
+                                        s.push(c);
+                                        i += end;
+                                    }
+                                    _ => s.push_str("\\u"),
+                                }
+                            }
+                            Some(&c) => s.push(c),
+                            None => {}
+                        }
+                    } else {
+                        s.push(chars[i]);
+                    }
+                    i += 1;
+                }
+                out.push(s);
+                i += 1;
+            }
+            _ => i += 1,
+        }
+    }
+    out
+}
+
+/// 把字符表编译成 Rust 源码。所有字符都必须能在字库中找到，否则返回 `Missing`
+pub fn compile(
+    source: &dyn GlyphSource,
+    chars: &[char],
+    opts: &Options,
+) -> Result<String, CompileError> {
+    if opts.h == 0 || !opts.h.is_multiple_of(8) || opts.w == 0 || opts.w > 255 || opts.h > 255 {
+        return Err(CompileError::Size(format!("{}x{}", opts.w, opts.h)));
+    }
+
+    let mut chars = chars.to_vec();
+    chars.sort_unstable();
+    chars.dedup();
+
+    let mut missing = Vec::new();
+    let mut glyphs = Vec::with_capacity(chars.len());
+    for &c in &chars {
+        match source.glyph(c, opts.w, opts.h) {
+            Some(bitmap) => glyphs.push(bitmap.to_pages()),
+            None => missing.push(c),
+        }
+    }
+    if !missing.is_empty() {
+        return Err(CompileError::Missing(missing));
+    }
+
+    let len = opts.h / 8 * opts.w;
+    let name = &opts.name;
+    let mut out = String::new();
+    // 写入 String 不会失败
+    let _ = writeln!(out, "// 由 fontc 生成，请勿手动修改");
+    let _ = writeln!(out, "// {}x{}，共 {} 个字符\n", opts.h, opts.w, chars.len());
+
+    let mut imports = Vec::new();
+    if let Some(base) = &opts.base {
+        imports.extend(["Font", base.as_str()]);
+    }
+    if opts.proportional {
+        imports.push("GlyphMetrics");
+    }
+    if !imports.is_empty() {
+        let _ = writeln!(
+            out,
+            "use {}::font::{{{}}};\n",
+            opts.crate_path,
+            imports.join(", ")
+        );
+    }
+
+    // 已排序的字符索引，Font 用二分查找定位点阵
+    let _ = write!(out, "pub const {}_CHARS: [char; {}] = [", name, chars.len());
+    for (i, c) in chars.iter().enumerate() {
+        if i % 12 == 0 {
+            out.push_str("\n    ");
+        } else {
+            out.push(' ');
+        }
+        let _ = write!(out, "'\\u{{{:04x}}}',", *c as u32);
+    }
+    out.push_str("\n];\n\n");
+
+    let _ = writeln!(
+        out,
+        "pub static {}_GLYPHS: [[u8; {}]; {}] = [",
+        name,
+        len,
+        chars.len()
+    );
+    for (i, (c, bytes)) in chars.iter().zip(&glyphs).enumerate() {
+        out.push_str("    [");
+        for (j, b) in bytes.iter().enumerate() {
+            if j % 15 == 0 {
+                out.push_str("\n        ");
+            } else {
+                out.push(' ');
+            }
+            let _ = write!(out, "0x{:02X},", b);
+        }
+        let _ = writeln!(out, "\n    ], /*\"{}\",{}*/", c, i);
+    }
+    out.push_str("];\n");
+
+    if opts.proportional {
+        let _ = writeln!(
+            out,
+            "\npub const {}_METRICS: [GlyphMetrics; {}] = [",
+            name,
+            chars.len()
+        );
+        for (c, bytes) in chars.iter().zip(&glyphs) {
+            let m = GlyphMetrics::measure(bytes, opts.h / 8, opts.w, 1);
+            let _ = writeln!(
+                out,
+                "    GlyphMetrics {{ left: {}, width: {}, bearing: {}, advance: {} }}, // {}",
+                m.left, m.width, m.bearing, m.advance, c
+            );
+        }
+        out.push_str("];\n");
+    }
+
+    if let Some(base) = &opts.base {
+        let _ = write!(
+            out,
+            "\npub const {}: Font = {}.with_glyphs({}, {}, &{}_CHARS, {}_GLYPHS.as_flattened())",
+            name, base, opts.w, opts.h, name, name
+        );
+        if opts.proportional {
+            let _ = write!(out, "\n    .with_glyph_metrics(&{}_METRICS)", name);
+        }
+        out.push_str(";\n");
+    }
+
+    Ok(out)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    struct Blank;
+
+    impl GlyphSource for Blank {
+        fn glyph(&self, _ch: char, w: usize, h: usize) -> Option<Bitmap> {
+            Some(Bitmap::new(w, h))
+        }
+    }
+
+    #[test]
+    fn string_literals_skip_comments() {
+        let source = r#"
+            // "行注释"
+            let a = "温度";
+            /* "块注释" /* "嵌套" */ "仍在注释中" */
+            ], /*"中",0*/
+            let b = r"湿度";
+            let c = '"';
+        "#;
+        assert_eq!(string_literals(source), ["温度", "湿度"]);
+    }
+
+    #[test]
+    fn malformed_unicode_escapes_stay_verbatim() {
+        let source = r#"
+            let a = "\u{6e29}度";
+            let b = "\u";
+            let c = "\uab";
+            let d = "\u{zz}";
+            let e = "}";
+        "#;
+        assert_eq!(
+            string_literals(source),
+            ["温度", "\\u", "\\uab", "\\u{zz}", "}"]
+        );
+    }
+
+    #[test]
+    fn generated_font_passes_its_height() {
+        let opts = Options {
+            name: "CJK16".to_string(),
+            w: 16,
+            h: 16,
+            base: Some("FONT16X16".to_string()),
+            crate_path: "crate".to_string(),
+            proportional: false,
+        };
+        let out = compile(&Blank, &['中'], &opts).unwrap();
+        assert!(out.contains(
+            "pub const CJK16: Font = FONT16X16.with_glyphs(16, 16, &CJK16_CHARS, CJK16_GLYPHS.as_flattened());"
+        ));
+        // 生成的源码中注释里的字符不会被再次收集
+        assert!(string_literals(&out).is_empty());
+    }
+
+    #[test]
+    #[should_panic(expected = "glyph height differs from the base font")]
+    fn with_glyphs_rejects_other_heights() {
+        let _ = crate::font::FONT8X8.with_glyphs(16, 16, &[], &[]);
+    }
+}
@@ -0,0 +1,203 @@
+use super::{FontFace, Glyph, GlyphMetrics, SharedFont};
+use std::fmt;
+
+/// 字体链中都找不到字符时的处理方式
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum MissingGlyph {
+    /// 不画也不占宽度，`print_string` 不把它当作错误
+    Skip,
+    /// 不画，`print_string` 在返回的错误中列出它
+    Error,
+    /// 用链中能找到的另一个字符代替，例如 `'?'`；代替字符也没有时画方框
+    Replace(char),
+    /// 画一个空心方框（豆腐块）
+    Tofu,
+}
+
+/// 字体不支持的字符
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct MissingGlyphs(pub Vec<char>);
+
+impl fmt::Display for MissingGlyphs {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "unsupported characters:")?;
+        for c in &self.0 {
+            write!(f, " U+{:04X}", *c as u32)?;
+        }
+        Ok(())
+    }
+}
+
+impl std::error::Error for MissingGlyphs {}
+
+/// 字体回退链：依次在各个字体中查找字符，例如先找中文字体，再找ASCII字体，
+/// 最后按 `MissingGlyph` 处理。链中第一个字体决定行高
+pub struct Fallback {
+    fonts: Vec<SharedFont>,
+    missing: MissingGlyph,
+    tofu: Vec<u8>, // 预先生成的方框点阵，绘制时直接借用
+}
+
+impl Fallback {
+    pub fn new(fonts: Vec<SharedFont>) -> Self {
+        let mut fallback = Fallback {
+            fonts,
+            missing: MissingGlyph::Tofu,
+            tofu: Vec::new(),
+        };
+        fallback.tofu = fallback.build_tofu();
+        fallback
+    }
+
+    /// 设置找不到字符时的处理方式，默认画方框
+    pub fn missing(mut self, missing: MissingGlyph) -> Self {
+        self.missing = missing;
+        self
+    }
+
+    pub fn push(&mut self, font: SharedFont) {
+        self.fonts.push(font);
+        self.tofu = self.build_tofu();
+    }
+
+    // 第一个包含该字符的字体
+    fn find(&self, ch: char) -> Option<&SharedFont> {
+        self.fonts.iter().find(|f| f.get_metrics(ch).is_some())
+    }
+
+    // 方框宽度为行高的一半
+    fn tofu_width(&self) -> u8 {
+        (self.get_font_height() / 2).max(4)
+    }
+
+    fn build_tofu(&self) -> Vec<u8> {
+        let h = self.get_font_height() as usize;
+        let w = self.tofu_width() as usize;
+        let mut pages = vec![0u8; w * h.div_ceil(8)];
+        // 上下各留一行，右侧留一列作为字间距
+        let (top, bottom) = (1, h.saturating_sub(2));
+        for row in top..=bottom {
+            let bit = 1 << (row % 8);
+            let page = &mut pages[row / 8 * w..(row / 8 + 1) * w];
+            if row == top || row == bottom {
+                page[..w - 1].iter_mut().for_each(|b| *b |= bit);
+            } else {
+                page[0] |= bit;
+                page[w - 2] |= bit;
+            }
+        }
+        pages
+    }
+
+    fn tofu(&self) -> Glyph<'_> {
+        Glyph::new(&self.tofu, self.tofu_width() as usize)
+    }
+}
+
+impl FontFace for Fallback {
+    fn get_glyph(&self, ch: char) -> Option<Glyph<'_>> {
+        if let Some(font) = self.find(ch) {
+            return font.get_glyph(ch);
+        }
+        if ch.is_control() {
+            return None;
+        }
+        match self.missing {
+            MissingGlyph::Skip | MissingGlyph::Error => None,
+            MissingGlyph::Replace(r) => match self.find(r) {
+                Some(font) => font.get_glyph(r),
+                None => Some(self.tofu()),
+            },
+            MissingGlyph::Tofu => Some(self.tofu()),
+        }
+    }
+
+    fn get_metrics(&self, ch: char) -> Option<GlyphMetrics> {
+        if let Some(font) = self.find(ch) {
+            return font.get_metrics(ch);
+        }
+        if ch.is_control() {
+            return None;
+        }
+        let tofu = GlyphMetrics::monospace(self.tofu_width());
+        match self.missing {
+            // 宽度为0的字符：渲染时什么也不画，光标不动
+            MissingGlyph::Skip => Some(GlyphMetrics::monospace(0)),
+            MissingGlyph::Error => None,
+            MissingGlyph::Replace(r) => {
+                Some(self.find(r).and_then(|f| f.get_metrics(r)).unwrap_or(tofu))
+            }
+            MissingGlyph::Tofu => Some(tofu),
+        }
+    }
+
+    fn get_font_height(&self) -> u8 {
+        self.fonts.first().map_or(8, |f| f.get_font_height())
+    }
+
+    fn get_kerning(&self, left: char, right: char) -> i8 {
+        // 只有两个字符来自同一个字体时才调整字距
+        match (self.find(left), self.find(right)) {
+            (Some(a), Some(b)) if std::sync::Arc::ptr_eq(a, b) => a.get_kerning(left, right),
+            _ => 0,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::font::FONT8X8;
+    use crate::image::Image;
+    use crate::text::TextStyle;
+    use crate::{HEIGHT, WIDTH, frame_lock, newframe, print_string, print_string_styled};
+    use std::sync::Arc;
+
+    fn font(missing: MissingGlyph) -> Fallback {
+        Fallback::new(vec![Arc::new(FONT8X8)]).missing(missing)
+    }
+
+    fn render(font: &Fallback, s: &str) -> (Result<(), MissingGlyphs>, Image) {
+        newframe();
+        let result = print_string(0, 0, font, s);
+        (result, Image::from_frame())
+    }
+
+    #[test]
+    fn skip_is_silent_and_takes_no_space() {
+        let _lock = frame_lock();
+        let (result, skipped) = render(&font(MissingGlyph::Skip), "a\u{4e2d}b");
+        assert_eq!(result, Ok(()));
+        let (_, plain) = render(&font(MissingGlyph::Skip), "ab");
+        assert_eq!(skipped, plain);
+
+        newframe();
+        let style = TextStyle {
+            bold: true,
+            invert: true,
+            ..TextStyle::PLAIN
+        };
+        let font = font(MissingGlyph::Skip);
+        assert_eq!(print_string_styled(0, 0, &font, "\u{4e2d}", style), Ok(()));
+        assert_eq!(Image::from_frame(), Image::new(WIDTH, HEIGHT));
+    }
+
+    #[test]
+    fn error_lists_missing_characters() {
+        let _lock = frame_lock();
+        let (result, _) = render(&font(MissingGlyph::Error), "a\u{4e2d}b\u{6587}");
+        assert_eq!(result, Err(MissingGlyphs(vec!['\u{4e2d}', '\u{6587}'])));
+    }
+
+    #[test]
+    fn tofu_and_replacement_are_drawn() {
+        let _lock = frame_lock();
+        let (result, tofu) = render(&font(MissingGlyph::Tofu), "\u{4e2d}");
+        assert_eq!(result, Ok(()));
+        assert_ne!(tofu, Image::new(WIDTH, HEIGHT));
+        let (result, replaced) = render(&font(MissingGlyph::Replace('?')), "\u{4e2d}");
+        assert_eq!(result, Ok(()));
+        let (_, question) = render(&font(MissingGlyph::Tofu), "?");
+        assert_eq!(replaced, question);
+    }
+}
@@ -956,3 +956,177 @@ pub const ASCII_24X12: [[u8; 36]; 95] = [
     ],
     /*"~",94*/
 ];
+
+// 内置字体附带的常用非ASCII符号，与ASCII部分同宽
+pub const SYMBOL_CHARS: [char; 1] = ['°'];
+
+pub const SYMBOLS_8X6: [[u8; 6]; 1] = [
+    [0x00, 0x06, 0x09, 0x09, 0x06, 0x00], // °
+];
+
+pub const SYMBOLS_16X8: [[u8; 16]; 1] = [[
+    0x00, 0x3C, 0x66, 0x42, 0x42, 0x66, 0x3C, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
+] /*"°",0*/];
+
+pub const SYMBOLS_24X12: [[u8; 36]; 1] = [[
+    0x00, 0x00, 0x78, 0xFC, 0x86, 0x86, 0x86, 0x86, 0xFC, 0x78, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
+    0x01, 0x01, 0x01, 0x01, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
+    0x00, 0x00, 0x00, 0x00,
+] /*"°",0*/];
+
+// 图标，位于 Unicode 私用区 U+E000 起，顺序与 font::Icon 一致
+pub const ICON_CHARS: [char; 24] = [
+    '\u{e000}', '\u{e001}', '\u{e002}', '\u{e003}', '\u{e004}', '\u{e005}', '\u{e006}', '\u{e007}',
+    '\u{e008}', '\u{e009}', '\u{e00a}', '\u{e00b}', '\u{e00c}', '\u{e00d}', '\u{e00e}', '\u{e00f}',
+    '\u{e010}', '\u{e011}', '\u{e012}', '\u{e013}', '\u{e014}', '\u{e015}', '\u{e016}', '\u{e017}',
+];
+
+pub const ICONS_8X8: [[u8; 8]; 24] = [
+    [0x7E, 0x42, 0x42, 0x42, 0x42, 0x42, 0x7E, 0x18], // battery-empty
+    [0x7E, 0x7E, 0x42, 0x42, 0x42, 0x42, 0x7E, 0x18], // battery-low
+    [0x7E, 0x7E, 0x7E, 0x7E, 0x42, 0x42, 0x7E, 0x18], // battery-half
+    [0x7E, 0x7E, 0x7E, 0x7E, 0x7E, 0x7E, 0x7E, 0x18], // battery-full
+    [0x7E, 0x42, 0x72, 0x5A, 0x4E, 0x42, 0x7E, 0x18], // battery-charging
+    [0x00, 0x00, 0x00, 0xC0, 0xC0, 0x00, 0x00, 0x00], // wifi-0
+    [0x00, 0x20, 0x10, 0xD0, 0xD0, 0x10, 0x20, 0x00], // wifi-1
+    [0x08, 0x24, 0x14, 0xD4, 0xD4, 0x14, 0x24, 0x08], // wifi-2
+    [0x02, 0x09, 0x25, 0x95, 0x95, 0x25, 0x09, 0x02], // wifi-3
+    [0x60, 0x78, 0x6B, 0x0F, 0x6B, 0x78, 0x60, 0x00], // network
+    [0x78, 0x7E, 0x79, 0x49, 0x79, 0x7E, 0x78, 0x00], // lock
+    [0x78, 0x7E, 0x79, 0x49, 0x79, 0x7A, 0x78, 0x00], // unlock
+    [0x08, 0x0C, 0x7E, 0x7F, 0x7E, 0x0C, 0x08, 0x00], // arrow-up
+    [0x08, 0x18, 0x3F, 0x7F, 0x3F, 0x18, 0x08, 0x00], // arrow-down
+    [0x08, 0x1C, 0x3E, 0x7F, 0x1C, 0x1C, 0x1C, 0x00], // arrow-left
+    [0x1C, 0x1C, 0x1C, 0x7F, 0x3E, 0x1C, 0x08, 0x00], // arrow-right
+    [0x18, 0x30, 0x60, 0x30, 0x18, 0x0C, 0x06, 0x00], // check
+    [0x22, 0x36, 0x1C, 0x08, 0x1C, 0x36, 0x22, 0x00], // cross
+    [0xE0, 0xF8, 0xFE, 0xA3, 0xFE, 0xF8, 0xE0, 0x00], // warning
+    [0x1C, 0x3E, 0x77, 0x45, 0x7F, 0x3E, 0x1C, 0x00], // info
+    [0x24, 0x18, 0xFF, 0x5A, 0x24, 0x00, 0x00, 0x00], // bluetooth
+    [0x60, 0xFE, 0xF9, 0xFE, 0x60, 0x00, 0x00, 0x00], // thermometer
+    [0x1C, 0x22, 0x41, 0x4F, 0x49, 0x22, 0x1C, 0x00], // clock
+    [0x1C, 0x22, 0x40, 0x4F, 0x40, 0x22, 0x1C, 0x00], // power
+];
+
+pub const ICONS_16X16: [[u8; 32]; 24] = [
+    [
+        0xFC, 0xFC, 0x0C, 0x0C, 0x0C, 0x0C, 0x0C, 0x0C, 0x0C, 0x0C, 0x0C, 0x0C, 0xFC, 0xFC, 0xC0,
+        0xC0, 0x3F, 0x3F, 0x30, 0x30, 0x30, 0x30, 0x30, 0x30, 0x30, 0x30, 0x30, 0x30, 0x3F, 0x3F,
+        0x03, 0x03,
+    ], /*"battery-empty",0*/
+    [
+        0xFC, 0xFC, 0xFC, 0xFC, 0x0C, 0x0C, 0x0C, 0x0C, 0x0C, 0x0C, 0x0C, 0x0C, 0xFC, 0xFC, 0xC0,
+        0xC0, 0x3F, 0x3F, 0x3F, 0x3F, 0x30, 0x30, 0x30, 0x30, 0x30, 0x30, 0x30, 0x30, 0x3F, 0x3F,
+        0x03, 0x03,
+    ], /*"battery-low",1*/
+    [
+        0xFC, 0xFC, 0xFC, 0xFC, 0xFC, 0xFC, 0xFC, 0xFC, 0x0C, 0x0C, 0x0C, 0x0C, 0xFC, 0xFC, 0xC0,
+        0xC0, 0x3F, 0x3F, 0x3F, 0x3F, 0x3F, 0x3F, 0x3F, 0x3F, 0x30, 0x30, 0x30, 0x30, 0x3F, 0x3F,
+        0x03, 0x03,
+    ], /*"battery-half",2*/
+    [
+        0xFC, 0xFC, 0xFC, 0xFC, 0xFC, 0xFC, 0xFC, 0xFC, 0xFC, 0xFC, 0xFC, 0xFC, 0xFC, 0xFC, 0xC0,
+        0xC0, 0x3F, 0x3F, 0x3F, 0x3F, 0x3F, 0x3F, 0x3F, 0x3F, 0x3F, 0x3F, 0x3F, 0x3F, 0x3F, 0x3F,
+        0x03, 0x03,
+    ], /*"battery-full",3*/
+    [
+        0xFC, 0xFC, 0x0C, 0x0C, 0x0C, 0x0C, 0xCC, 0xCC, 0xFC, 0xFC, 0x0C, 0x0C, 0xFC, 0xFC, 0xC0,
+        0xC0, 0x3F, 0x3F, 0x30, 0x30, 0x3F, 0x3F, 0x33, 0x33, 0x30, 0x30, 0x30, 0x30, 0x3F, 0x3F,
+        0x03, 0x03,
+    ], /*"battery-charging",4*/
+    [
+        0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
+        0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x60, 0xF0, 0xF0, 0x60, 0x00, 0x00, 0x00, 0x00,
+        0x00, 0x00,
+    ], /*"wifi-0",5*/
+    [
+        0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
+        0x00, 0x00, 0x00, 0x0C, 0x0E, 0x07, 0x03, 0x63, 0xF3, 0xF3, 0x63, 0x03, 0x07, 0x0E, 0x0C,
+        0x00, 0x00,
+    ], /*"wifi-1",6*/
+    [
+        0xC0, 0xE0, 0x70, 0x30, 0x30, 0x30, 0x30, 0x30, 0x30, 0x30, 0x30, 0x30, 0x30, 0x70, 0xE0,
+        0xC0, 0x00, 0x00, 0x0C, 0x0E, 0x07, 0x03, 0x63, 0xF3, 0xF3, 0x63, 0x03, 0x07, 0x0E, 0x0C,
+        0x00, 0x00,
+    ], /*"wifi-2",7*/
+    [
+        0x0C, 0x0E, 0xC7, 0xE3, 0x73, 0x33, 0x33, 0x33, 0x33, 0x33, 0x33, 0x73, 0xE3, 0xC7, 0x0E,
+        0x0C, 0x00, 0x00, 0x00, 0x00, 0x0C, 0x0E, 0xC7, 0xC3, 0xC3, 0xC7, 0x0E, 0x0C, 0x00, 0x00,
+        0x00, 0x00,
+    ], /*"wifi-3",8*/
+    [
+        0x00, 0x00, 0xC0, 0xC0, 0xCF, 0xCF, 0xFF, 0xFF, 0xCF, 0xCF, 0xC0, 0xC0, 0x00, 0x00, 0x00,
+        0x00, 0x3C, 0x3C, 0x3F, 0x3F, 0x3C, 0x3C, 0x00, 0x00, 0x3C, 0x3C, 0x3F, 0x3F, 0x3C, 0x3C,
+        0x00, 0x00,
+    ], /*"network",9*/
+    [
+        0xC0, 0xC0, 0xFC, 0xFC, 0xC3, 0xC3, 0xC3, 0xC3, 0xC3, 0xC3, 0xFC, 0xFC, 0xC0, 0xC0, 0x00,
+        0x00, 0x3F, 0x3F, 0x3F, 0x3F, 0x3F, 0x3F, 0x30, 0x30, 0x3F, 0x3F, 0x3F, 0x3F, 0x3F, 0x3F,
+        0x00, 0x00,
+    ], /*"lock",10*/
+    [
+        0xC0, 0xC0, 0xFC, 0xFC, 0xC3, 0xC3, 0xC3, 0xC3, 0xC3, 0xC3, 0xCC, 0xCC, 0xC0, 0xC0, 0x00,
+        0x00, 0x3F, 0x3F, 0x3F, 0x3F, 0x3F, 0x3F, 0x30, 0x30, 0x3F, 0x3F, 0x3F, 0x3F, 0x3F, 0x3F,
+        0x00, 0x00,
+    ], /*"unlock",11*/
+    [
+        0xC0, 0xE0, 0xE0, 0xF8, 0xF8, 0xFE, 0xFF, 0xFF, 0xFE, 0xF8, 0xF8, 0xE0, 0xE0, 0xC0, 0x00,
+        0x00, 0x00, 0x00, 0x00, 0x01, 0x1F, 0x3F, 0x3F, 0x3F, 0x3F, 0x1F, 0x01, 0x00, 0x00, 0x00,
+        0x00, 0x00,
+    ], /*"arrow-up",12*/
+    [
+        0xC0, 0xC0, 0xC0, 0xE0, 0xFE, 0xFF, 0xFF, 0xFF, 0xFF, 0xFE, 0xE0, 0xC0, 0xC0, 0xC0, 0x00,
+        0x00, 0x00, 0x01, 0x01, 0x07, 0x07, 0x1F, 0x3F, 0x3F, 0x1F, 0x07, 0x07, 0x01, 0x01, 0x00,
+        0x00, 0x00,
+    ], /*"arrow-down",13*/
+    [
+        0xC0, 0xE0, 0xE0, 0xF8, 0xF8, 0xFE, 0xFF, 0xFF, 0xF8, 0xF0, 0xF0, 0xF0, 0xF0, 0xE0, 0x00,
+        0x00, 0x00, 0x01, 0x01, 0x07, 0x07, 0x1F, 0x3F, 0x3F, 0x07, 0x03, 0x03, 0x03, 0x03, 0x01,
+        0x00, 0x00,
+    ], /*"arrow-left",14*/
+    [
+        0xE0, 0xF0, 0xF0, 0xF0, 0xF0, 0xF8, 0xFF, 0xFF, 0xFE, 0xF8, 0xF8, 0xE0, 0xE0, 0xC0, 0x00,
+        0x00, 0x01, 0x03, 0x03, 0x03, 0x03, 0x07, 0x3F, 0x3F, 0x1F, 0x07, 0x07, 0x01, 0x01, 0x00,
+        0x00, 0x00,
+    ], /*"arrow-right",15*/
+    [
+        0xC0, 0xC0, 0x80, 0x00, 0x00, 0x00, 0x00, 0x80, 0x80, 0xE0, 0xE0, 0x78, 0x7C, 0x1C, 0x00,
+        0x00, 0x01, 0x07, 0x07, 0x1E, 0x3C, 0x3C, 0x1E, 0x07, 0x07, 0x01, 0x01, 0x00, 0x00, 0x00,
+        0x00, 0x00,
+    ], /*"check",16*/
+    [
+        0x0C, 0x1C, 0x1C, 0x38, 0xF8, 0xE0, 0xC0, 0xC0, 0xE0, 0xF8, 0x38, 0x1C, 0x1C, 0x0C, 0x00,
+        0x00, 0x0C, 0x0E, 0x0E, 0x07, 0x07, 0x01, 0x00, 0x00, 0x01, 0x07, 0x07, 0x0E, 0x0E, 0x0C,
+        0x00, 0x00,
+    ], /*"cross",17*/
+    [
+        0x00, 0x00, 0x80, 0xE0, 0xF8, 0xFE, 0x0F, 0x0F, 0xFE, 0xF8, 0xE0, 0x80, 0x00, 0x00, 0x00,
+        0x00, 0x78, 0xFE, 0xFF, 0xFF, 0xFF, 0xFF, 0xCC, 0xCC, 0xFF, 0xFF, 0xFF, 0xFF, 0xFE, 0x78,
+        0x00, 0x00,
+    ], /*"warning",18*/
+    [
+        0xE0, 0xF8, 0xF8, 0xFE, 0x3E, 0x3F, 0x33, 0x73, 0xFF, 0xFE, 0xFE, 0xF8, 0xF8, 0xE0, 0x00,
+        0x00, 0x01, 0x07, 0x07, 0x1F, 0x1F, 0x3E, 0x30, 0x30, 0x3F, 0x1F, 0x1F, 0x07, 0x07, 0x01,
+        0x00, 0x00,
+    ], /*"info",19*/
+    [
+        0x30, 0x70, 0x80, 0xC0, 0xFF, 0xFF, 0xCE, 0x8C, 0x78, 0x30, 0x00, 0x00, 0x00, 0x00, 0x00,
+        0x00, 0x0C, 0x0E, 0x01, 0x03, 0xFF, 0xFF, 0x73, 0x31, 0x1E, 0x0C, 0x00, 0x00, 0x00, 0x00,
+        0x00, 0x00,
+    ], /*"bluetooth",20*/
+    [
+        0x00, 0x00, 0xFC, 0xFE, 0xC3, 0xC3, 0xFE, 0xFC, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
+        0x00, 0x18, 0x7E, 0x7F, 0xFF, 0xFF, 0xFF, 0xFF, 0x7F, 0x7E, 0x18, 0x00, 0x00, 0x00, 0x00,
+        0x00, 0x00,
+    ], /*"thermometer",21*/
+    [
+        0xF0, 0xF8, 0x1C, 0x0E, 0x03, 0x03, 0x7F, 0xFF, 0xE3, 0xC3, 0x0E, 0x1C, 0xF8, 0xF0, 0x00,
+        0x00, 0x03, 0x07, 0x0E, 0x1C, 0x38, 0x30, 0x30, 0x30, 0x30, 0x38, 0x1C, 0x0E, 0x07, 0x03,
+        0x00, 0x00,
+    ], /*"clock",22*/
+    [
+        0xF0, 0xF8, 0x1C, 0x0C, 0x00, 0x00, 0xFF, 0xFF, 0x00, 0x00, 0x0C, 0x1C, 0xF8, 0xF0, 0x00,
+        0x00, 0x03, 0x07, 0x0E, 0x1C, 0x38, 0x30, 0x30, 0x30, 0x30, 0x38, 0x1C, 0x0E, 0x07, 0x03,
+        0x00, 0x00,
+    ], /*"power",23*/
+];
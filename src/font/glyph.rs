@@ -0,0 +1,61 @@
+use std::borrow::Cow;
+
+/// 字符点阵的只读视图，按页排列：第 `i` 页是 `width` 个字节，字节的低位在上。
+///
+/// 内置字体直接借用静态表中的数据，绘制时不会分配内存；
+/// 放大、方框等合成字形才持有自己的数据。
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Glyph<'a> {
+    data: Cow<'a, [u8]>,
+    width: usize,
+}
+
+impl<'a> Glyph<'a> {
+    /// 借用页格式的点阵数据，长度必须是 `width` 的整数倍
+    pub const fn new(data: &'a [u8], width: usize) -> Self {
+        Glyph {
+            data: Cow::Borrowed(data),
+            width,
+        }
+    }
+
+    pub fn from_vec(data: Vec<u8>, width: usize) -> Glyph<'static> {
+        Glyph {
+            data: Cow::Owned(data),
+            width,
+        }
+    }
+
+    /// 每页的列数
+    pub fn width(&self) -> usize {
+        self.width
+    }
+
+    /// 页数（高度除以8）
+    pub fn page_count(&self) -> usize {
+        self.data.len().checked_div(self.width).unwrap_or(0)
+    }
+
+    pub fn page(&self, index: usize) -> Option<&[u8]> {
+        self.data.get(index * self.width..(index + 1) * self.width)
+    }
+
+    /// 逐页遍历列数据
+    pub fn pages(&self) -> impl Iterator<Item = &[u8]> {
+        self.data.chunks_exact(self.width.max(1))
+    }
+
+    /// (col, row) 处是否点亮，越界时返回 `false`
+    pub fn pixel(&self, col: usize, row: usize) -> bool {
+        col < self.width
+            && self
+                .data
+                .get(row / 8 * self.width + col)
+                .is_some_and(|b| b & (1 << (row % 8)) != 0)
+    }
+
+    /// 复制为逐页的二维数组
+    pub fn to_vec(&self) -> Vec<Vec<u8>> {
+        self.pages().map(<[u8]>::to_vec).collect()
+    }
+}
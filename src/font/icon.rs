@@ -0,0 +1,217 @@
+use super::{Fallback, ICONS8X8, ICONS16X16, MissingGlyph, Scaled, SharedFont};
+use std::fmt;
+use std::sync::Arc;
+
+/// 内置图标，8x8 和 16x16 两种尺寸分别在 `ICONS8X8` 和 `ICONS16X16` 中，
+/// 用 `with_icons` 接到文字字体后面才能与文字混排。
+///
+/// 每个图标对应 Unicode 私用区 U+E000 起的一个码位，可以直接写在字符串里：
+/// `format!("{} 87%", Icon::BatteryFull)`
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum Icon {
+    BatteryEmpty,
+    BatteryLow,
+    BatteryHalf,
+    BatteryFull,
+    BatteryCharging,
+    Wifi0,
+    Wifi1,
+    Wifi2,
+    Wifi3,
+    Network,
+    Lock,
+    Unlock,
+    ArrowUp,
+    ArrowDown,
+    ArrowLeft,
+    ArrowRight,
+    Check,
+    Cross,
+    Warning,
+    Info,
+    Bluetooth,
+    Thermometer,
+    Clock,
+    Power,
+}
+
+/// 第一个图标的码位
+pub const ICON_BASE: u32 = 0xE000;
+
+impl Icon {
+    pub const ALL: [Icon; 24] = [
+        Icon::BatteryEmpty,
+        Icon::BatteryLow,
+        Icon::BatteryHalf,
+        Icon::BatteryFull,
+        Icon::BatteryCharging,
+        Icon::Wifi0,
+        Icon::Wifi1,
+        Icon::Wifi2,
+        Icon::Wifi3,
+        Icon::Network,
+        Icon::Lock,
+        Icon::Unlock,
+        Icon::ArrowUp,
+        Icon::ArrowDown,
+        Icon::ArrowLeft,
+        Icon::ArrowRight,
+        Icon::Check,
+        Icon::Cross,
+        Icon::Warning,
+        Icon::Info,
+        Icon::Bluetooth,
+        Icon::Thermometer,
+        Icon::Clock,
+        Icon::Power,
+    ];
+
+    /// 图标对应的私用区字符
+    pub const fn char(self) -> char {
+        match char::from_u32(ICON_BASE + self as u32) {
+            Some(c) => c,
+            None => '?',
+        }
+    }
+
+    pub fn from_char(ch: char) -> Option<Icon> {
+        let index = (ch as u32).checked_sub(ICON_BASE)?;
+        Icon::ALL.get(index as usize).copied()
+    }
+
+    /// 图标名称，例如 `battery-full`、`wifi-2`
+    pub fn name(self) -> &'static str {
+        match self {
+            Icon::BatteryEmpty => "battery-empty",
+            Icon::BatteryLow => "battery-low",
+            Icon::BatteryHalf => "battery-half",
+            Icon::BatteryFull => "battery-full",
+            Icon::BatteryCharging => "battery-charging",
+            Icon::Wifi0 => "wifi-0",
+            Icon::Wifi1 => "wifi-1",
+            Icon::Wifi2 => "wifi-2",
+            Icon::Wifi3 => "wifi-3",
+            Icon::Network => "network",
+            Icon::Lock => "lock",
+            Icon::Unlock => "unlock",
+            Icon::ArrowUp => "arrow-up",
+            Icon::ArrowDown => "arrow-down",
+            Icon::ArrowLeft => "arrow-left",
+            Icon::ArrowRight => "arrow-right",
+            Icon::Check => "check",
+            Icon::Cross => "cross",
+            Icon::Warning => "warning",
+            Icon::Info => "info",
+            Icon::Bluetooth => "bluetooth",
+            Icon::Thermometer => "thermometer",
+            Icon::Clock => "clock",
+            Icon::Power => "power",
+        }
+    }
+
+    /// 按名称查找图标，也接受几个常用的简写：`temp`、`ok`、`warn`、`bt`、`eth`
+    pub fn from_name(name: &str) -> Option<Icon> {
+        let name = match name {
+            "temp" => "thermometer",
+            "ok" => "check",
+            "warn" => "warning",
+            "bt" => "bluetooth",
+            "eth" => "network",
+            _ => name,
+        };
+        Icon::ALL.iter().copied().find(|i| i.name() == name)
+    }
+
+    /// 按电量百分比选择电池图标
+    pub fn battery(percent: u8) -> Icon {
+        match percent {
+            0..=10 => Icon::BatteryEmpty,
+            11..=40 => Icon::BatteryLow,
+            41..=80 => Icon::BatteryHalf,
+            _ => Icon::BatteryFull,
+        }
+    }
+
+    /// 按信号格数（0-3）选择 Wi-Fi 图标
+    pub fn wifi(bars: u8) -> Icon {
+        match bars {
+            0 => Icon::Wifi0,
+            1 => Icon::Wifi1,
+            2 => Icon::Wifi2,
+            _ => Icon::Wifi3,
+        }
+    }
+}
+
+impl fmt::Display for Icon {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.char())
+    }
+}
+
+/// 在字体后面接上同样高度的图标，返回的 `Fallback` 中字体自己有的字符优先，
+/// 两者都没有的字符和原来一样在 `print_string` 的错误中列出。
+/// 行高为16的倍数时用 16x16 图标放大，否则用 8x8 图标放大；行高不是8的倍数时原样返回
+pub fn with_icons(font: SharedFont) -> SharedFont {
+    let icons: SharedFont = match font.get_font_height() {
+        8 => Arc::new(ICONS8X8),
+        16 => Arc::new(ICONS16X16),
+        h if h > 0 && h % 16 == 0 => Arc::new(Scaled::new(ICONS16X16, h / 16)),
+        h if h > 0 && h % 8 == 0 => Arc::new(Scaled::new(ICONS8X8, h / 8)),
+        _ => return font,
+    };
+    Arc::new(Fallback::new(vec![font, icons]).missing(MissingGlyph::Error))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::font::{FONT8X8, FONT16X16_PROP, FONT24X24, FontFace};
+
+    #[test]
+    fn icons_are_a_separate_face() {
+        assert!(FONT8X8.get_metrics(Icon::Lock.char()).is_none());
+        assert!(ICONS8X8.get_metrics('A').is_none());
+        assert!(ICONS8X8.get_glyph('A').is_none());
+        assert_eq!(ICONS16X16.get_char_width(Icon::Lock.char()), Some(16));
+    }
+
+    #[test]
+    fn with_icons_matches_the_font_height() {
+        for (font, h) in [
+            (Arc::new(FONT8X8) as SharedFont, 8),
+            (Arc::new(FONT16X16_PROP), 16),
+            (Arc::new(FONT24X24), 24),
+        ] {
+            let font = with_icons(font);
+            assert_eq!(font.get_font_height(), h);
+            let glyph = font.get_glyph(Icon::Check.char()).unwrap();
+            assert_eq!(glyph.page_count() * 8, h as usize);
+            assert_eq!(font.get_char_width(Icon::Check.char()), Some(h));
+            // 字体自己的字符和缺字处理不变
+            assert!(font.get_metrics('A').is_some());
+            assert!(font.get_metrics('\u{4e2d}').is_none());
+        }
+        // 比例字体的字距仍然有效
+        let font = with_icons(Arc::new(FONT16X16_PROP));
+        assert_eq!(font.get_kerning('A', 'V'), -1);
+    }
+
+    #[test]
+    fn print_string_needs_with_icons() {
+        use crate::font::{FontRegistry, MissingGlyphs};
+        use crate::{frame_lock, newframe, print_string};
+
+        let _lock = frame_lock();
+        newframe();
+        let text = format!("{} 87%", Icon::BatteryFull);
+        assert_eq!(
+            print_string(0, 0, &FONT8X8, &text),
+            Err(MissingGlyphs(vec![Icon::BatteryFull.char()]))
+        );
+        let font = with_icons(Arc::new(FONT8X8));
+        assert_eq!(print_string(0, 0, font.as_ref(), &text), Ok(()));
+        let font = FontRegistry::with_builtin().get("8x8").unwrap();
+        assert_eq!(print_string(0, 0, font.as_ref(), &text), Ok(()));
+    }
+}
@@ -2,21 +2,201 @@
 // #[allow(dead_code)]
 
 use std::ptr;
+#[cfg(feature = "fontc")]
+pub mod compiler;
+mod fallback;
 mod font_bytes;
-use font_bytes::{ASCII_8X6, ASCII_16X8, ASCII_24X12, NONE_CHAR};
+mod glyph;
+mod icon;
+mod registry;
+mod scale;
+pub use fallback::{Fallback, MissingGlyph, MissingGlyphs};
+use font_bytes::{
+    ASCII_8X6, ASCII_16X8, ASCII_24X12, ICON_CHARS, ICONS_8X8, ICONS_16X16, NONE_CHAR,
+    SYMBOL_CHARS, SYMBOLS_8X6, SYMBOLS_16X8, SYMBOLS_24X12,
+};
+pub use glyph::Glyph;
+pub use icon::{ICON_BASE, Icon, with_icons};
+pub use registry::{FontRegistry, SharedFont};
+pub use scale::Scaled;
 struct ASCIIFont {
     h: u8,
     w: u8,
     chars: &'static [u8],
+    metrics: &'static [GlyphMetrics], // 为空时等宽
 }
 
 pub struct Font {
     h: u8,
     w: u8,
-    chars: &'static [char],
+    chars: &'static [char],           // 非ASCII字符表，按升序排列以便二分查找
+    glyphs: &'static [u8],            // 与 chars 一一对应的点阵，每个字符 h / 8 * w 字节
+    metrics: &'static [GlyphMetrics], // 与 chars 一一对应，为空时等宽
+    kerning: &'static [KernPair],     // 按 (left, right) 升序排列
     ascii: ASCIIFont,
 }
 
+/// 比例字体中单个字符的度量信息
+///
+/// 字符点阵仍然按固定的字符格存储，绘制时只使用 `left..left + width` 这几列，
+/// 第一列落在光标右侧 `bearing` 处，之后光标前进 `advance`。
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct GlyphMetrics {
+    pub left: u8,    // 点阵中第一个有效列
+    pub width: u8,   // 有效列数
+    pub bearing: i8, // 左侧间距
+    pub advance: u8, // 光标前进距离
+}
+
+impl GlyphMetrics {
+    /// 等宽字体的度量：绘制整个字符格
+    pub const fn monospace(w: u8) -> Self {
+        GlyphMetrics {
+            left: 0,
+            width: w,
+            bearing: 0,
+            advance: w,
+        }
+    }
+
+    /// 根据点阵中实际点亮的列计算度量，`spacing` 为字符之间的间距。
+    /// 空白字符（如空格）宽度为0，前进字符格宽度的一半
+    pub const fn measure(data: &[u8], rows: usize, cols: usize, spacing: u8) -> Self {
+        let mut first = cols;
+        let mut last = 0;
+        let mut col = 0;
+        while col < cols {
+            let mut row = 0;
+            while row < rows {
+                if data[row * cols + col] != 0 {
+                    if col < first {
+                        first = col;
+                    }
+                    last = col;
+                }
+                row += 1;
+            }
+            col += 1;
+        }
+        if first == cols {
+            return GlyphMetrics {
+                left: 0,
+                width: 0,
+                bearing: 0,
+                advance: (cols as u8).div_ceil(2),
+            };
+        }
+        let width = (last - first + 1) as u8;
+        GlyphMetrics {
+            left: first as u8,
+            width,
+            bearing: 0,
+            advance: width + spacing,
+        }
+    }
+}
+
+/// 字体接口：渲染函数通过它取字形，应用可以实现自己的字体
+pub trait FontFace {
+    /// 字符点阵，内置字体返回借用静态表的视图
+    fn get_glyph(&self, ch: char) -> Option<Glyph<'_>>;
+    fn get_metrics(&self, ch: char) -> Option<GlyphMetrics>;
+    fn get_font_height(&self) -> u8;
+
+    fn get_kerning(&self, _left: char, _right: char) -> i8 {
+        0
+    }
+
+    fn get_char_width(&self, ch: char) -> Option<u8> {
+        self.get_metrics(ch).map(|m| m.advance)
+    }
+
+    /// 字符点阵的二维数组副本，按页排列
+    fn get_char(&self, ch: char) -> Option<Vec<Vec<u8>>> {
+        self.get_glyph(ch).map(|g| g.to_vec())
+    }
+}
+
+impl<T: FontFace + ?Sized> FontFace for &T {
+    fn get_glyph(&self, ch: char) -> Option<Glyph<'_>> {
+        (**self).get_glyph(ch)
+    }
+
+    fn get_metrics(&self, ch: char) -> Option<GlyphMetrics> {
+        (**self).get_metrics(ch)
+    }
+
+    fn get_font_height(&self) -> u8 {
+        (**self).get_font_height()
+    }
+
+    fn get_kerning(&self, left: char, right: char) -> i8 {
+        (**self).get_kerning(left, right)
+    }
+}
+
+impl<T: FontFace + ?Sized> FontFace for std::sync::Arc<T> {
+    fn get_glyph(&self, ch: char) -> Option<Glyph<'_>> {
+        (**self).get_glyph(ch)
+    }
+
+    fn get_metrics(&self, ch: char) -> Option<GlyphMetrics> {
+        (**self).get_metrics(ch)
+    }
+
+    fn get_font_height(&self) -> u8 {
+        (**self).get_font_height()
+    }
+
+    fn get_kerning(&self, left: char, right: char) -> i8 {
+        (**self).get_kerning(left, right)
+    }
+}
+
+impl FontFace for Font {
+    fn get_glyph(&self, ch: char) -> Option<Glyph<'_>> {
+        Font::get_glyph(self, ch)
+    }
+
+    fn get_metrics(&self, ch: char) -> Option<GlyphMetrics> {
+        Font::get_metrics(self, ch)
+    }
+
+    fn get_font_height(&self) -> u8 {
+        Font::get_font_height(self)
+    }
+
+    fn get_kerning(&self, left: char, right: char) -> i8 {
+        Font::get_kerning(self, left, right)
+    }
+}
+
+/// 字距调整：`left` 后面紧跟 `right` 时，光标额外移动 `adjust` 列（通常为负）
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct KernPair {
+    pub left: char,
+    pub right: char,
+    pub adjust: i8,
+}
+
+// 计算整张ASCII表的度量
+const fn measure_table<const N: usize>(
+    chars: &[u8],
+    rows: usize,
+    cols: usize,
+) -> [GlyphMetrics; N] {
+    let mut out = [GlyphMetrics::monospace(0); N];
+    let len = rows * cols;
+    let mut i = 0;
+    while i < N {
+        let (_, rest) = chars.split_at(i * len);
+        let (data, _) = rest.split_at(len);
+        out[i] = GlyphMetrics::measure(data, rows, cols, 1);
+        i += 1;
+    }
+    out
+}
+
 #[allow(dead_code)]
 pub struct Buffer<'a> {
     pub data: Vec<&'a str>,
@@ -24,67 +204,119 @@ pub struct Buffer<'a> {
 }
 
 impl Font {
-    pub fn get_char(&self, ch: char) -> Option<Vec<Vec<u8>>> {
-        // if !ch.is_ascii() || ch < ' ' || ch > 'z' {
-        //     return None;
-        // }
-        let (char_data, rows, cols) = if ch.is_ascii() && ch < ' ' {
-            return None;
+    /// 字符点阵，直接借用静态字库中的数据，不分配内存
+    pub fn get_glyph(&self, ch: char) -> Option<Glyph<'static>> {
+        if ch.is_ascii() && ch < ' ' {
+            None
         } else if ch.is_ascii() {
-            let index = (ch as u8 - ' ' as u8) as usize;
+            let index = (ch as u8 - b' ') as usize;
             let len: usize = ((self.ascii.h / 8) * self.ascii.w) as usize;
-            let char_data = &self.ascii.chars[index * len..(index + 1) * len];
-            // 确定行数（高度除以8，因为每个字节代表8个垂直像素）
-            let rows = (self.ascii.h / 8) as usize;
-            let cols = self.ascii.w as usize;
-            (char_data, rows, cols)
-        } else {
+            let char_data = self.ascii.chars.get(index * len..(index + 1) * len)?;
+            Some(Glyph::new(char_data, self.ascii.w as usize))
+        } else if let Ok(index) = self.chars.binary_search(&ch) {
             // 处理非ASCII字符
-            // if let Some(index) = self.chars.iter().position(|&c| c == ch) {
-            //     let len = ((self.h / 8) * self.w) as usize;
-            //     let char_data = self.chars[index * len..(index + 1) * len]
-            //         .iter()
-            //         .map(|&c| c as u8)
-            //         .collect::<Vec<u8>>()
-            //         .as_slice();
-            //     let rows = (self.h / 8) as usize;
-            //     let cols = self.w as usize;
-            //     (char_data, rows, cols)
-            println!("Unsupported character: {}", ch);
-            // } else {
-            return None;
-            // }
-        };
-
-        // 创建二维数组
-        let mut result: Vec<Vec<u8>> = Vec::with_capacity(rows);
-        for row in 0..rows {
-            let mut row_data: Vec<u8> = Vec::with_capacity(cols);
-            for col in 0..cols {
-                row_data.push(char_data[row * cols + col]);
-            }
-            result.push(row_data);
+            let len = (self.h / 8) as usize * self.w as usize;
+            let char_data = self.glyphs.get(index * len..(index + 1) * len)?;
+            Some(Glyph::new(char_data, self.w as usize))
+        } else {
+            None
         }
-        Some(result)
     }
 
+    /// 字符点阵的二维数组副本，绘制时请使用不分配内存的 `get_glyph`
+    pub fn get_char(&self, ch: char) -> Option<Vec<Vec<u8>>> {
+        self.get_glyph(ch).map(|g| g.to_vec())
+    }
+
+    /// 字符的前进宽度，比例字体中每个字符各不相同
     pub fn get_char_width(&self, ch: char) -> Option<u8> {
+        self.get_metrics(ch).map(|m| m.advance)
+    }
+
+    pub fn get_metrics(&self, ch: char) -> Option<GlyphMetrics> {
         if ch.is_ascii() {
-            if ch < ' ' {
+            // 图标字体等没有ASCII表的字体
+            if ch < ' ' || self.ascii.chars.is_empty() {
                 return None;
             }
-            return Some(self.ascii.w);
-        } else {
-            if self.chars.iter().any(|&c| c == ch) {
-                return Some(self.w);
+            let index = (ch as u8 - b' ') as usize;
+            // 有的ASCII表不完整（如 8x6 没有 `|}~`），表外的字符与 `get_glyph` 一样不支持
+            let len = (self.ascii.h / 8) as usize * self.ascii.w as usize;
+            if (index + 1) * len > self.ascii.chars.len() {
+                return None;
+            }
+            match self.ascii.metrics.get(index) {
+                Some(&m) => Some(m),
+                None => Some(GlyphMetrics::monospace(self.ascii.w)),
             }
-            return None;
+        } else if let Ok(index) = self.chars.binary_search(&ch) {
+            match self.metrics.get(index) {
+                Some(&m) => Some(m),
+                None => Some(GlyphMetrics::monospace(self.w)),
+            }
+        } else {
+            None
         }
     }
 
+    /// 两个相邻字符之间的字距调整
+    pub fn get_kerning(&self, left: char, right: char) -> i8 {
+        self.kerning
+            .binary_search_by(|k| (k.left, k.right).cmp(&(left, right)))
+            .map_or(0, |i| self.kerning[i].adjust)
+    }
+
     pub fn get_font_height(&self) -> u8 {
         self.h
     }
+
+    /// 在现有字体上挂载一组非ASCII字符点阵（通常由 `fontc` 生成）。
+    ///
+    /// `chars` 必须按升序排列，`glyphs` 中每个字符占 `h / 8 * w` 字节，
+    /// 排列方式与 font_bytes.rs 中的ASCII表相同（按页，每页 `w` 列）。
+    /// 行高由基础字体决定，`h` 与它不同时在编译期报错。基础字体原有的非ASCII字符
+    /// 及其度量被替换，比例字体之后再用 `with_glyph_metrics` 设置度量。
+    pub const fn with_glyphs(
+        self,
+        w: u8,
+        h: u8,
+        chars: &'static [char],
+        glyphs: &'static [u8],
+    ) -> Font {
+        assert!(h == self.h, "glyph height differs from the base font");
+        Font {
+            w,
+            chars,
+            glyphs,
+            metrics: &[],
+            ..self
+        }
+    }
+
+    /// 设置ASCII部分的度量，使字体成为比例字体
+    pub const fn with_ascii_metrics(self, metrics: &'static [GlyphMetrics]) -> Font {
+        Font {
+            ascii: ASCIIFont {
+                metrics,
+                ..self.ascii
+            },
+            ..self
+        }
+    }
+
+    /// 设置非ASCII字符的度量，与 `with_glyphs` 中的 `chars` 一一对应
+    pub const fn with_glyph_metrics(self, metrics: &'static [GlyphMetrics]) -> Font {
+        Font { metrics, ..self }
+    }
+
+    /// 设置字距调整表，`kerning` 必须按 `(left, right)` 升序排列
+    pub const fn with_kerning(self, kerning: &'static [KernPair]) -> Font {
+        Font { kerning, ..self }
+    }
+
+    pub fn is_proportional(&self) -> bool {
+        !self.ascii.metrics.is_empty() || !self.metrics.is_empty()
+    }
 }
 
 struct Image {
@@ -101,12 +333,18 @@ const AFONT8X6: ASCIIFont = ASCIIFont {
             .as_ref()
             .unwrap()
     },
+    metrics: &[],
 };
 
+/// 8 像素高的内置字体，ASCII 字符宽 6 列，非ASCII部分只有几个常用符号（如 `°`）。
+/// 不含图标，需要 `Icon` 时用 `with_icons` 包装，或者从 `FontRegistry::with_builtin` 中取 `8x8`
 pub const FONT8X8: Font = Font {
     h: 8,
-    w: 8,
-    chars: &NONE_CHAR,
+    w: 6,
+    chars: &SYMBOL_CHARS,
+    glyphs: SYMBOLS_8X6.as_flattened(),
+    metrics: &[],
+    kerning: &[],
     ascii: AFONT8X6,
 };
 
@@ -118,12 +356,17 @@ const AFONT16X8: ASCIIFont = ASCIIFont {
             .as_ref()
             .unwrap()
     },
+    metrics: &[],
 };
 
+/// 16 像素高的内置字体，ASCII 字符宽 8 列，同样不含图标（见 `FONT8X8`）
 pub const FONT16X16: Font = Font {
     h: 16,
-    w: 16,
-    chars: &NONE_CHAR,
+    w: 8,
+    chars: &SYMBOL_CHARS,
+    glyphs: SYMBOLS_16X8.as_flattened(),
+    metrics: &[],
+    kerning: &[],
     ascii: AFONT16X8,
 };
 
@@ -135,11 +378,187 @@ const AFONT24X12: ASCIIFont = ASCIIFont {
             .as_ref()
             .unwrap()
     },
+    metrics: &[],
 };
 
+/// 24 像素高的内置字体，ASCII 字符宽 12 列，同样不含图标（见 `FONT8X8`）
 pub const FONT24X24: Font = Font {
     h: 24,
-    w: 24,
-    chars: &NONE_CHAR,
+    w: 12,
+    chars: &SYMBOL_CHARS,
+    glyphs: SYMBOLS_24X12.as_flattened(),
+    metrics: &[],
+    kerning: &[],
     ascii: AFONT24X12,
 };
+
+// 图标字体没有ASCII部分
+const fn no_ascii(h: u8) -> ASCIIFont {
+    ASCIIFont {
+        h,
+        w: h,
+        chars: &[],
+        metrics: &[],
+    }
+}
+
+/// 8x8 图标，只包含 `Icon` 中的字符，用 `with_icons` 接到文字字体后面
+pub const ICONS8X8: Font = Font {
+    h: 8,
+    w: 8,
+    chars: &ICON_CHARS,
+    glyphs: ICONS_8X8.as_flattened(),
+    metrics: &[],
+    kerning: &[],
+    ascii: no_ascii(8),
+};
+
+/// 16x16 图标，同 `ICONS8X8`
+pub const ICONS16X16: Font = Font {
+    h: 16,
+    w: 16,
+    chars: &ICON_CHARS,
+    glyphs: ICONS_16X16.as_flattened(),
+    metrics: &[],
+    kerning: &[],
+    ascii: no_ascii(16),
+};
+
+// 常用的字距调整，按 (left, right) 排序
+const KERNING_BASIC: [KernPair; 12] = [
+    KernPair {
+        left: 'A',
+        right: 'T',
+        adjust: -1,
+    },
+    KernPair {
+        left: 'A',
+        right: 'V',
+        adjust: -1,
+    },
+    KernPair {
+        left: 'A',
+        right: 'Y',
+        adjust: -1,
+    },
+    KernPair {
+        left: 'L',
+        right: 'T',
+        adjust: -1,
+    },
+    KernPair {
+        left: 'L',
+        right: 'Y',
+        adjust: -1,
+    },
+    KernPair {
+        left: 'T',
+        right: 'A',
+        adjust: -1,
+    },
+    KernPair {
+        left: 'T',
+        right: 'a',
+        adjust: -1,
+    },
+    KernPair {
+        left: 'T',
+        right: 'e',
+        adjust: -1,
+    },
+    KernPair {
+        left: 'T',
+        right: 'o',
+        adjust: -1,
+    },
+    KernPair {
+        left: 'V',
+        right: 'A',
+        adjust: -1,
+    },
+    KernPair {
+        left: 'Y',
+        right: 'A',
+        adjust: -1,
+    },
+    KernPair {
+        left: 'r',
+        right: '.',
+        adjust: -1,
+    },
+];
+
+const METRICS8X6: [GlyphMetrics; 92] = measure_table(AFONT8X6.chars, 1, 6);
+const METRICS16X8: [GlyphMetrics; 95] = measure_table(AFONT16X8.chars, 2, 8);
+const METRICS24X12: [GlyphMetrics; 95] = measure_table(AFONT24X12.chars, 3, 12);
+const SYMBOL_METRICS8X6: [GlyphMetrics; 1] = measure_table(SYMBOLS_8X6.as_flattened(), 1, 6);
+const SYMBOL_METRICS16X8: [GlyphMetrics; 1] = measure_table(SYMBOLS_16X8.as_flattened(), 2, 8);
+const SYMBOL_METRICS24X12: [GlyphMetrics; 1] = measure_table(SYMBOLS_24X12.as_flattened(), 3, 12);
+
+/// 比例字体版本：每个字符只占用实际笔画的宽度
+pub const FONT8X8_PROP: Font = FONT8X8
+    .with_ascii_metrics(&METRICS8X6)
+    .with_glyph_metrics(&SYMBOL_METRICS8X6)
+    .with_kerning(&KERNING_BASIC);
+
+pub const FONT16X16_PROP: Font = FONT16X16
+    .with_ascii_metrics(&METRICS16X8)
+    .with_glyph_metrics(&SYMBOL_METRICS16X8)
+    .with_kerning(&KERNING_BASIC);
+
+pub const FONT24X24_PROP: Font = FONT24X24
+    .with_ascii_metrics(&METRICS24X12)
+    .with_glyph_metrics(&SYMBOL_METRICS24X12)
+    .with_kerning(&KERNING_BASIC);
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn ascii_past_the_table_is_missing() {
+        for font in [FONT8X8, FONT8X8_PROP] {
+            assert!(font.get_metrics('{').is_some());
+            assert_eq!(font.get_metrics('|'), None);
+            assert_eq!(font.get_metrics('~'), None);
+            assert!(font.get_glyph('~').is_none());
+        }
+        assert!(FONT16X16.get_metrics('~').is_some());
+    }
+
+    #[test]
+    fn proportional_advances() {
+        // 等宽字体每个字符占整格
+        assert_eq!(FONT8X8.get_char_width('i'), Some(6));
+        assert_eq!(FONT16X16.get_char_width('i'), Some(8));
+        for font in [FONT8X8_PROP, FONT16X16_PROP, FONT24X24_PROP] {
+            assert!(font.is_proportional());
+            let (i, m) = (
+                font.get_metrics('i').unwrap(),
+                font.get_metrics('m').unwrap(),
+            );
+            // 前进宽度为笔画宽度加 1 列间距，窄字符比宽字符窄
+            assert_eq!(i.advance, i.width + 1);
+            assert_eq!(m.advance, m.width + 1);
+            assert!(i.advance < m.advance);
+            // 空格没有笔画，前进半格
+            let space = font.get_metrics(' ').unwrap();
+            assert_eq!(space.width, 0);
+            assert_eq!(space.advance, font.ascii.w.div_ceil(2));
+            assert!(font.get_metrics('°').unwrap().width > 0);
+        }
+        assert!(!FONT8X8.is_proportional());
+    }
+
+    #[test]
+    fn kerning_pairs() {
+        for font in [FONT8X8_PROP, FONT16X16_PROP, FONT24X24_PROP] {
+            assert_eq!(font.get_kerning('A', 'V'), -1);
+            assert_eq!(font.get_kerning('r', '.'), -1);
+            // 字距只对给定顺序生效
+            assert_eq!(font.get_kerning('V', 'V'), 0);
+            assert_eq!(font.get_kerning('.', 'r'), 0);
+        }
+        assert_eq!(FONT8X8.get_kerning('A', 'V'), 0);
+    }
+}
@@ -0,0 +1,71 @@
+use super::{
+    FONT8X8, FONT8X8_PROP, FONT16X16, FONT16X16_PROP, FONT24X24, FONT24X24_PROP, FontFace,
+    with_icons,
+};
+use std::collections::BTreeMap;
+use std::sync::Arc;
+
+/// 共享的字体对象，可以在线程之间传递
+pub type SharedFont = Arc<dyn FontFace + Send + Sync>;
+
+/// 按名称管理字体，应用可以注册自己的字体而不用修改本库
+#[derive(Clone, Default)]
+pub struct FontRegistry {
+    fonts: BTreeMap<String, SharedFont>,
+}
+
+impl FontRegistry {
+    /// 空的字体表
+    pub fn new() -> Self {
+        FontRegistry {
+            fonts: BTreeMap::new(),
+        }
+    }
+
+    /// 包含内置字体的字体表：`8x8`、`16x16`、`24x24` 及其比例字体版本 `*-prop`，
+    /// 都已用 `with_icons` 接上图标
+    pub fn with_builtin() -> Self {
+        let mut registry = FontRegistry::new();
+        let builtin = [
+            ("8x8", FONT8X8),
+            ("16x16", FONT16X16),
+            ("24x24", FONT24X24),
+            ("8x8-prop", FONT8X8_PROP),
+            ("16x16-prop", FONT16X16_PROP),
+            ("24x24-prop", FONT24X24_PROP),
+        ];
+        for (name, font) in builtin {
+            registry.register_shared(name, with_icons(Arc::new(font)));
+        }
+        registry
+    }
+
+    /// 注册字体，同名字体会被替换
+    pub fn register(&mut self, name: &str, font: impl FontFace + Send + Sync + 'static) {
+        self.fonts.insert(name.to_string(), Arc::new(font));
+    }
+
+    pub fn register_shared(&mut self, name: &str, font: SharedFont) {
+        self.fonts.insert(name.to_string(), font);
+    }
+
+    pub fn get(&self, name: &str) -> Option<SharedFont> {
+        self.fonts.get(name).cloned()
+    }
+
+    /// 按字体高度查找，名称排序后的第一个匹配项优先
+    pub fn by_height(&self, height: u8) -> Option<SharedFont> {
+        self.fonts
+            .values()
+            .find(|f| f.get_font_height() == height)
+            .cloned()
+    }
+
+    pub fn remove(&mut self, name: &str) -> Option<SharedFont> {
+        self.fonts.remove(name)
+    }
+
+    pub fn names(&self) -> impl Iterator<Item = &str> {
+        self.fonts.keys().map(String::as_str)
+    }
+}
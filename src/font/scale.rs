@@ -0,0 +1,166 @@
+use super::{FontFace, Glyph, GlyphMetrics};
+
+/// 整数倍放大的字体，用于大号时钟、状态数字等。
+///
+/// 点阵按最近邻放大后重新排成页格式；开启 `smooth` 后会把放大产生的
+/// 阶梯状斜边补成斜线（类似 EPX 算法）。
+pub struct Scaled<F> {
+    font: F,
+    factor: u8,
+    smooth: bool,
+}
+
+impl<F: FontFace> Scaled<F> {
+    /// `factor` 为放大倍数，放大后的字体高度不能超过255
+    pub fn new(font: F, factor: u8) -> Self {
+        let max = 255 / font.get_font_height().max(1);
+        Scaled {
+            font,
+            factor: factor.clamp(1, max),
+            smooth: false,
+        }
+    }
+
+    /// 开启斜边平滑
+    pub fn smooth(mut self, smooth: bool) -> Self {
+        self.smooth = smooth;
+        self
+    }
+
+    pub fn factor(&self) -> u8 {
+        self.factor
+    }
+}
+
+// 在放大后的 k x k 方块中填充一个角上的直角三角形
+fn fill_corner(
+    out: &mut [u8],
+    out_w: usize,
+    (x0, y0): (usize, usize),
+    k: usize,
+    (flip_x, flip_y): (bool, bool),
+) {
+    for i in 0..k {
+        for j in 0..k - 1 - i {
+            let dx = if flip_x { k - 1 - j } else { j };
+            let dy = if flip_y { k - 1 - i } else { i };
+            let (sx, sy) = (x0 + dx, y0 + dy);
+            out[sy / 8 * out_w + sx] |= 1 << (sy % 8);
+        }
+    }
+}
+
+impl<F: FontFace> FontFace for Scaled<F> {
+    fn get_glyph(&self, ch: char) -> Option<Glyph<'_>> {
+        let glyph = self.font.get_glyph(ch)?;
+        let k = self.factor as usize;
+        let h = glyph.page_count() * 8;
+        let w = glyph.width();
+        let pixel = |x: isize, y: isize| x >= 0 && y >= 0 && glyph.pixel(x as usize, y as usize);
+
+        // 放大后的字形是新数据，只能由自己持有
+        let out_w = w * k;
+        let mut out = vec![0u8; out_w * (h * k / 8)];
+        for y in 0..h {
+            for x in 0..w {
+                let (xi, yi) = (x as isize, y as isize);
+                if pixel(xi, yi) {
+                    for dy in 0..k {
+                        for dx in 0..k {
+                            let (sx, sy) = (x * k + dx, y * k + dy);
+                            out[sy / 8 * out_w + sx] |= 1 << (sy % 8);
+                        }
+                    }
+                    continue;
+                }
+                if !self.smooth {
+                    continue;
+                }
+                // 空白点的两个相邻点亮点构成斜边时，补上靠近它们的三角形
+                let up = pixel(xi, yi - 1);
+                let down = pixel(xi, yi + 1);
+                let left = pixel(xi - 1, yi);
+                let right = pixel(xi + 1, yi);
+                for (a, b, other_a, other_b, flip_x, flip_y) in [
+                    (up, left, down, right, false, false),
+                    (up, right, down, left, true, false),
+                    (down, left, up, right, false, true),
+                    (down, right, up, left, true, true),
+                ] {
+                    if a && b && !other_a && !other_b {
+                        fill_corner(&mut out, out_w, (x * k, y * k), k, (flip_x, flip_y));
+                    }
+                }
+            }
+        }
+        Some(Glyph::from_vec(out, out_w))
+    }
+
+    fn get_metrics(&self, ch: char) -> Option<GlyphMetrics> {
+        let m = self.font.get_metrics(ch)?;
+        let k = self.factor;
+        Some(GlyphMetrics {
+            left: m.left.saturating_mul(k),
+            width: m.width.saturating_mul(k),
+            bearing: m.bearing.saturating_mul(k as i8),
+            advance: m.advance.saturating_mul(k),
+        })
+    }
+
+    fn get_font_height(&self) -> u8 {
+        self.font.get_font_height() * self.factor
+    }
+
+    fn get_kerning(&self, left: char, right: char) -> i8 {
+        self.font
+            .get_kerning(left, right)
+            .saturating_mul(self.factor as i8)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::font::{FONT8X8, FONT24X24, Font};
+
+    // 2 列宽的对角线：(0, 0) 和 (1, 1) 两个点
+    const DIAGONAL: Font = FONT8X8.with_glyphs(2, 8, &['α'], &[0b01, 0b10]);
+
+    fn pages(font: &impl FontFace) -> Vec<Vec<u8>> {
+        font.get_char('α').unwrap()
+    }
+
+    #[test]
+    fn nearest_neighbour_expansion() {
+        assert_eq!(
+            pages(&Scaled::new(DIAGONAL, 2)),
+            [[0b11, 0b11, 0b1100, 0b1100], [0; 4]]
+        );
+        assert_eq!(
+            pages(&Scaled::new(DIAGONAL, 3)),
+            [
+                vec![0b111, 0b111, 0b111, 0b111000, 0b111000, 0b111000],
+                vec![0; 6],
+                vec![0; 6]
+            ]
+        );
+    }
+
+    #[test]
+    fn factor_keeps_height_within_u8() {
+        let big = Scaled::new(FONT8X8, 100);
+        assert_eq!((big.factor(), big.get_font_height()), (31, 248));
+        let big = Scaled::new(FONT24X24, 100);
+        assert_eq!((big.factor(), big.get_font_height()), (10, 240));
+        assert_eq!(Scaled::new(FONT8X8, 0).factor(), 1);
+    }
+
+    #[test]
+    fn smoothing_fills_the_step() {
+        // 两个方块之间的空白角各补上一个点：(2, 1) 和 (1, 2)
+        assert_eq!(
+            pages(&Scaled::new(DIAGONAL, 2).smooth(true)),
+            [[0b11, 0b111, 0b1110, 0b1100], [0; 4]]
+        );
+    }
+}
@@ -0,0 +1,510 @@
+//! 本地 HTTP/JSON 接口：把请求转换成 `daemon::Request`，和 Unix 套接字的客户端共用同一块屏幕
+//!
+//! ```text
+//! POST /text      {"region": "screen", "text": "Hello", "font": "8x8", "align": "center", "valign": "middle"}
+//! POST /pixel     {"region": "main", "x": 1, "y": 2, "on": true}
+//! POST /line      {"x0": 0, "y0": 0, "x1": 127, "y1": 63}
+//! POST /rect      {"x": 0, "y": 0, "w": 20, "h": 10, "fill": true}
+//! POST /circle    {"x": 64, "y": 32, "r": 10}
+//! POST /clear     {"region": "main"}，请求体为空时清空整个屏幕
+//! POST /region    {"name": "status", "x": 0, "y": 0, "w": 128, "h": 10}
+//! POST /image?region=main&x=0&y=0     请求体为 PBM 或 PNG
+//! POST /frame     请求体为 128x64 的 PBM 或 PNG
+//! POST /contrast  {"value": 128}
+//! POST /power     {"on": false}
+//! POST /invert    {"on": true}
+//! POST /flush
+//! GET  /frame     当前画面的 PNG，?format=pbm 时返回 PBM
+//! GET  /status    对比度、显示开关、反色和区域列表
+//! ```
+//!
+//! `region` 省略时为整个屏幕，`on` 省略时为点亮。查询参数按百分号编码解码，
+//! 请求体最大 1MiB。成功时返回 `{"ok":true}`，失败时返回 4xx 状态码和 `{"error":"原因"}`，
+//! 请求体过大时为 413。
+
+use crate::daemon::{self, Request, Screen, Server};
+use crate::image::Image;
+use crate::{HAlign, Rect, VAlign};
+use serde::Deserialize;
+use serde::de::DeserializeOwned;
+use serde_json::{Value, json};
+use std::io::{self, Cursor, Read};
+use std::sync::Mutex;
+use tiny_http::{Header, Method, Response};
+
+// 请求体的大小上限
+const MAX_BODY: u64 = 1 << 20;
+
+type Reply = Response<Cursor<Vec<u8>>>;
+
+fn default_region() -> String {
+    "screen".to_string()
+}
+
+fn default_font() -> String {
+    "8x8".to_string()
+}
+
+fn default_on() -> bool {
+    true
+}
+
+#[derive(Deserialize)]
+struct Text {
+    #[serde(default = "default_region")]
+    region: String,
+    text: String,
+    #[serde(default = "default_font")]
+    font: String,
+    #[serde(default)]
+    align: Option<String>,
+    #[serde(default)]
+    valign: Option<String>,
+}
+
+#[derive(Deserialize)]
+struct Pixel {
+    #[serde(default = "default_region")]
+    region: String,
+    x: i32,
+    y: i32,
+    #[serde(default = "default_on")]
+    on: bool,
+}
+
+#[derive(Deserialize)]
+struct Line {
+    #[serde(default = "default_region")]
+    region: String,
+    x0: i32,
+    y0: i32,
+    x1: i32,
+    y1: i32,
+    #[serde(default = "default_on")]
+    on: bool,
+}
+
+#[derive(Deserialize)]
+struct Shape {
+    #[serde(default = "default_region")]
+    region: String,
+    x: i32,
+    y: i32,
+    #[serde(default)]
+    w: i32,
+    #[serde(default)]
+    h: i32,
+    #[serde(default)]
+    r: i32,
+    #[serde(default)]
+    fill: bool,
+    #[serde(default = "default_on")]
+    on: bool,
+}
+
+#[derive(Deserialize)]
+struct Clear {
+    region: Option<String>,
+}
+
+#[derive(Deserialize)]
+struct Region {
+    name: String,
+    x: u8,
+    y: u8,
+    w: u8,
+    h: u8,
+}
+
+#[derive(Deserialize)]
+struct Contrast {
+    value: u8,
+}
+
+#[derive(Deserialize)]
+struct Switch {
+    on: bool,
+}
+
+fn json(status: u16, value: Value) -> Reply {
+    let header = Header::from_bytes("Content-Type", "application/json").unwrap();
+    Response::from_data(value.to_string().into_bytes())
+        .with_status_code(status)
+        .with_header(header)
+}
+
+fn data(content_type: &str, data: Vec<u8>) -> Reply {
+    let header = Header::from_bytes("Content-Type", content_type).unwrap();
+    Response::from_data(data).with_header(header)
+}
+
+// 百分号解码，`+` 当作空格；不合法的 `%` 序列原样保留
+fn percent_decode(s: &str) -> String {
+    let bytes = s.as_bytes();
+    let mut out = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+    while i < bytes.len() {
+        let hex = bytes
+            .get(i + 1..i + 3)
+            .and_then(|h| std::str::from_utf8(h).ok())
+            .and_then(|h| u8::from_str_radix(h, 16).ok());
+        match (bytes[i], hex) {
+            (b'%', Some(b)) => {
+                out.push(b);
+                i += 3;
+                continue;
+            }
+            (b'+', _) => out.push(b' '),
+            (b, _) => out.push(b),
+        }
+        i += 1;
+    }
+    String::from_utf8_lossy(&out).into_owned()
+}
+
+// 查询字符串中的参数，键和值都做百分号解码
+fn query(url: &str, key: &str) -> Option<String> {
+    let (_, query) = url.split_once('?')?;
+    query
+        .split('&')
+        .map(|pair| pair.split_once('=').unwrap_or((pair, "")))
+        .find(|(k, _)| percent_decode(k) == key)
+        .map(|(_, v)| percent_decode(v))
+}
+
+fn query_number(url: &str, key: &str) -> Result<i32, (u16, String)> {
+    query(url, key).map_or(Ok(0), |v| {
+        v.parse().map_err(|_| (400, format!("bad {}: {}", key, v)))
+    })
+}
+
+fn parse<T: DeserializeOwned>(body: &[u8]) -> Result<T, (u16, String)> {
+    serde_json::from_slice(body).map_err(|e| (400, e.to_string()))
+}
+
+fn align<T: std::str::FromStr<Err = String> + Default>(
+    s: Option<String>,
+) -> Result<T, (u16, String)> {
+    s.map_or(Ok(T::default()), |s| s.parse().map_err(|e| (400, e)))
+}
+
+/// 在地址（如 `127.0.0.1:8080`）上提供 HTTP 接口，直到出错才返回。
+/// 请求逐个处理，屏幕按守护进程的刷新间隔更新
+pub fn serve(server: &Server, addr: &str) -> io::Result<()> {
+    let http = tiny_http::Server::http(addr).map_err(io::Error::other)?;
+    server.start_refresh();
+    let screen = server.screen();
+    for mut request in http.incoming_requests() {
+        let reply = handle(&screen, &mut request)
+            .unwrap_or_else(|(status, e)| json(status, json!({ "error": e })));
+        // 客户端提前断开时忽略
+        let _ = request.respond(reply);
+    }
+    Ok(())
+}
+
+fn handle(
+    screen: &Mutex<Screen>,
+    request: &mut tiny_http::Request,
+) -> Result<Reply, (u16, String)> {
+    let url = request.url().to_string();
+    let path = url.split('?').next().unwrap_or("");
+    let mut body = Vec::new();
+    request
+        .as_reader()
+        .take(MAX_BODY + 1)
+        .read_to_end(&mut body)
+        .map_err(|e| (400, e.to_string()))?;
+    if body.len() as u64 > MAX_BODY {
+        return Err((413, format!("body larger than {} bytes", MAX_BODY)));
+    }
+    let lock = || {
+        screen
+            .lock()
+            .map_err(|_| (500, "display unavailable".to_string()))
+    };
+
+    let command = match (request.method(), path) {
+        (Method::Get, "/frame") => {
+            let image = {
+                let _screen = lock()?;
+                Image::from_frame()
+            };
+            return Ok(match query(&url, "format").as_deref() {
+                Some("pbm") => data("image/x-portable-bitmap", image.to_pbm()),
+                _ => data("image/png", image.to_png()),
+            });
+        }
+        (Method::Get, "/status") => return Ok(json(200, lock()?.state())),
+        (Method::Post, "/text") => {
+            let t: Text = parse(&body)?;
+            Request::Text {
+                region: t.region,
+                font: t.font,
+                align: align::<HAlign>(t.align)?,
+                valign: align::<VAlign>(t.valign)?,
+                text: t.text,
+            }
+        }
+        (Method::Post, "/pixel") => {
+            let p: Pixel = parse(&body)?;
+            Request::Pixel {
+                region: p.region,
+                x: p.x,
+                y: p.y,
+                color: p.on,
+            }
+        }
+        (Method::Post, "/line") => {
+            let l: Line = parse(&body)?;
+            Request::Line {
+                region: l.region,
+                from: (l.x0, l.y0),
+                to: (l.x1, l.y1),
+                color: l.on,
+            }
+        }
+        (Method::Post, "/rect") => {
+            let s: Shape = parse(&body)?;
+            Request::Rect {
+                region: s.region,
+                x: s.x,
+                y: s.y,
+                w: s.w,
+                h: s.h,
+                fill: s.fill,
+                color: s.on,
+            }
+        }
+        (Method::Post, "/circle") => {
+            let s: Shape = parse(&body)?;
+            Request::Circle {
+                region: s.region,
+                x: s.x,
+                y: s.y,
+                r: s.r,
+                color: s.on,
+            }
+        }
+        (Method::Post, "/clear") if body.is_empty() => Request::Clear(None),
+        (Method::Post, "/clear") => Request::Clear(parse::<Clear>(&body)?.region),
+        (Method::Post, "/region") => {
+            let r: Region = parse(&body)?;
+            Request::Region(r.name, Rect::new(r.x, r.y, r.w, r.h))
+        }
+        (Method::Post, "/image") => Request::Image {
+            region: query(&url, "region").unwrap_or_else(default_region),
+            x: query_number(&url, "x")?,
+            y: query_number(&url, "y")?,
+            data: body,
+        },
+        (Method::Post, "/frame") => Request::Frame(body),
+        (Method::Post, "/contrast") => Request::Contrast(parse::<Contrast>(&body)?.value),
+        (Method::Post, "/power") => Request::Power(parse::<Switch>(&body)?.on),
+        (Method::Post, "/invert") => Request::Invert(parse::<Switch>(&body)?.on),
+        (Method::Post, "/flush") => Request::Flush,
+        (
+            _,
+            "/frame" | "/status" | "/text" | "/pixel" | "/line" | "/rect" | "/circle" | "/clear"
+            | "/region" | "/image" | "/contrast" | "/power" | "/invert" | "/flush",
+        ) => return Err((405, "method not allowed".to_string())),
+        _ => return Err((404, format!("no such endpoint {}", path))),
+    };
+    daemon::execute(screen, command).map_err(|e| (400, e))?;
+    Ok(json(200, json!({ "ok": true })))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Display, HEIGHT, WIDTH, frame_lock};
+    use tiny_http::TestRequest;
+
+    fn call(
+        screen: &Mutex<Screen>,
+        method: Method,
+        path: &str,
+        body: &'static str,
+    ) -> (u16, Vec<u8>) {
+        let mut request = TestRequest::new()
+            .with_method(method)
+            .with_path(path)
+            .with_body(body)
+            .into();
+        let reply = handle(screen, &mut request)
+            .unwrap_or_else(|(status, e)| json(status, json!({ "error": e })));
+        (reply.status_code().0, reply.into_reader().into_inner())
+    }
+
+    fn screen() -> Mutex<Screen> {
+        Mutex::new(Screen::new(Display::simulated()))
+    }
+
+    #[test]
+    fn drawing_endpoints() {
+        let _lock = frame_lock();
+        let screen = screen();
+        let ok = (200, br#"{"ok":true}"#.to_vec());
+        assert_eq!(
+            call(
+                &screen,
+                Method::Post,
+                "/rect",
+                r#"{"x": 0, "y": 0, "w": 4, "h": 4, "fill": true}"#
+            ),
+            ok
+        );
+        assert_eq!(
+            call(
+                &screen,
+                Method::Post,
+                "/pixel",
+                r#"{"x": 1, "y": 1, "on": false}"#
+            ),
+            ok
+        );
+        let frame = Image::from_frame();
+        assert!(frame.pixel(0, 0) && frame.pixel(3, 3) && !frame.pixel(1, 1) && !frame.pixel(4, 4));
+
+        assert_eq!(call(&screen, Method::Post, "/clear", ""), ok);
+        assert_eq!(Image::from_frame(), Image::new(WIDTH, HEIGHT));
+        assert_eq!(
+            call(
+                &screen,
+                Method::Post,
+                "/text",
+                r#"{"text": "Hi", "align": "center"}"#
+            ),
+            ok
+        );
+        assert_ne!(Image::from_frame(), Image::new(WIDTH, HEIGHT));
+    }
+
+    #[test]
+    fn frame_endpoints() {
+        let _lock = frame_lock();
+        let screen = screen();
+        let mut image = Image::new(WIDTH, HEIGHT);
+        image.set(3, 4, true);
+        image.set(100, 60, true);
+        // P1 是文本格式，可以作为测试请求的请求体
+        let bits: Vec<String> = (0..HEIGHT)
+            .map(|y| {
+                (0..WIDTH)
+                    .map(|x| if image.pixel(x, y) { "1" } else { "0" })
+                    .collect()
+            })
+            .collect();
+        let pbm = format!("P1\n{} {}\n{}\n", WIDTH, HEIGHT, bits.join("\n"));
+        let (status, _) = call(&screen, Method::Post, "/frame", pbm.leak());
+        assert_eq!(status, 200);
+        assert_eq!(Image::from_frame(), image);
+
+        let (status, body) = call(&screen, Method::Get, "/frame", "");
+        assert_eq!(status, 200);
+        assert_eq!(Image::from_png(&body), Ok(image.clone()));
+        let (status, body) = call(&screen, Method::Get, "/frame?format=pbm", "");
+        assert_eq!(status, 200);
+        assert_eq!(body, image.to_pbm());
+    }
+
+    #[test]
+    fn status_endpoint() {
+        let _lock = frame_lock();
+        let screen = screen();
+        call(&screen, Method::Post, "/contrast", r#"{"value": 64}"#);
+        call(
+            &screen,
+            Method::Post,
+            "/region",
+            r#"{"name": "bar", "x": 0, "y": 0, "w": 128, "h": 8}"#,
+        );
+        let (status, body) = call(&screen, Method::Get, "/status", "");
+        assert_eq!(status, 200);
+        let state: Value = serde_json::from_slice(&body).unwrap();
+        assert_eq!(state["contrast"], 64);
+        assert_eq!(state["simulated"], true);
+        assert_eq!(state["regions"]["bar"], json!([0, 0, 128, 8]));
+    }
+
+    #[test]
+    fn error_statuses() {
+        let _lock = frame_lock();
+        let screen = screen();
+        assert_eq!(call(&screen, Method::Post, "/text", "not json").0, 400);
+        assert_eq!(
+            call(
+                &screen,
+                Method::Post,
+                "/text",
+                r#"{"text": "x", "align": "up"}"#
+            )
+            .0,
+            400
+        );
+        assert_eq!(
+            call(
+                &screen,
+                Method::Post,
+                "/pixel",
+                r#"{"region": "nope", "x": 0, "y": 0}"#
+            )
+            .0,
+            400
+        );
+        assert_eq!(
+            call(&screen, Method::Post, "/frame", "P1\n2 2\n1 0 1 0\n").0,
+            400
+        );
+        assert_eq!(call(&screen, Method::Post, "/image?x=abc", "").0, 400);
+        assert_eq!(call(&screen, Method::Get, "/nope", "").0, 404);
+        assert_eq!(call(&screen, Method::Get, "/text", "").0, 405);
+        let (status, body) = call(&screen, Method::Post, "/status", "");
+        assert_eq!(status, 405);
+        assert_eq!(body, br#"{"error":"method not allowed"}"#);
+    }
+
+    #[test]
+    fn oversized_body() {
+        let _lock = frame_lock();
+        let screen = screen();
+        let body = "x".repeat(MAX_BODY as usize + 1).leak();
+        let (status, reply) = call(&screen, Method::Post, "/frame", body);
+        assert_eq!(status, 413);
+        assert_eq!(reply, br#"{"error":"body larger than 1048576 bytes"}"#);
+    }
+
+    #[test]
+    fn query_is_percent_decoded() {
+        let url = "/image?region=top%20bar&x=%2D3&y=4+&flag&bad=%zz%4";
+        assert_eq!(query(url, "region").as_deref(), Some("top bar"));
+        assert_eq!(query(url, "y").as_deref(), Some("4 "));
+        assert_eq!(query(url, "flag").as_deref(), Some(""));
+        assert_eq!(query(url, "bad").as_deref(), Some("%zz%4"));
+        assert_eq!(query(url, "none"), None);
+        assert_eq!(query_number(url, "x"), Ok(-3));
+        assert_eq!(query_number(url, "none"), Ok(0));
+        assert_eq!(query("/frame", "format"), None);
+    }
+
+    #[test]
+    fn image_region_from_query() {
+        let _lock = frame_lock();
+        let screen = screen();
+        let region = r#"{"name": "top bar", "x": 8, "y": 8, "w": 4, "h": 4}"#;
+        assert_eq!(call(&screen, Method::Post, "/region", region).0, 200);
+        let (status, _) = call(
+            &screen,
+            Method::Post,
+            "/image?region=top%20bar&x=%2B1",
+            "P1\n2 1\n1 1\n",
+        );
+        assert_eq!(status, 200);
+        assert_eq!(call(&screen, Method::Post, "/flush", "").0, 200);
+        let frame = Image::from_frame();
+        assert!(!frame.pixel(8, 8));
+        assert!(frame.pixel(9, 8));
+        assert!(frame.pixel(10, 8));
+        assert!(!frame.pixel(11, 8));
+    }
+}
@@ -0,0 +1,283 @@
+use crate::setpixel;
+use std::fmt;
+
+// 解码时允许的最大像素数，远大于屏幕，只用来拒绝伪造的文件头
+const MAX_PIXELS: usize = 4096 * 4096;
+
+/// 图片解析错误
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum ImageError {
+    /// 不支持的格式或文件头错误
+    Format(String),
+    /// 数据比文件头声明的少
+    Truncated,
+}
+
+impl fmt::Display for ImageError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ImageError::Format(msg) => write!(f, "bad image: {}", msg),
+            ImageError::Truncated => write!(f, "image data truncated"),
+        }
+    }
+}
+
+impl std::error::Error for ImageError {}
+
+/// 单色图片，`true` 表示点亮
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Image {
+    width: usize,
+    height: usize,
+    pixels: Vec<bool>,
+}
+
+// PBM 文件头中的下一个数字，跳过空白和 # 注释
+fn header_number(data: &[u8], pos: &mut usize) -> Result<usize, ImageError> {
+    loop {
+        match data.get(*pos) {
+            Some(b'#') => {
+                while data.get(*pos).is_some_and(|&b| b != b'\n') {
+                    *pos += 1;
+                }
+            }
+            Some(b) if b.is_ascii_whitespace() => *pos += 1,
+            Some(_) => break,
+            None => return Err(ImageError::Truncated),
+        }
+    }
+    let start = *pos;
+    while data.get(*pos).is_some_and(u8::is_ascii_digit) {
+        *pos += 1;
+    }
+    std::str::from_utf8(&data[start..*pos])
+        .ok()
+        .and_then(|s| s.parse().ok())
+        .ok_or_else(|| ImageError::Format("expected a number in header".to_string()))
+}
+
+fn checked_size(width: usize, height: usize) -> Result<usize, ImageError> {
+    width
+        .checked_mul(height)
+        .filter(|&n| n <= MAX_PIXELS)
+        .ok_or_else(|| ImageError::Format(format!("image too large: {}x{}", width, height)))
+}
+
+impl Image {
+    pub fn new(width: usize, height: usize) -> Self {
+        Image {
+            width,
+            height,
+            pixels: vec![false; width * height],
+        }
+    }
+
+    /// 解析 PBM 图片（P1 文本格式或 P4 二进制格式），黑色像素点亮
+    pub fn from_pbm(data: &[u8]) -> Result<Image, ImageError> {
+        let raw = match data.get(..2) {
+            Some(b"P1") => false,
+            Some(b"P4") => true,
+            _ => return Err(ImageError::Format("not a PBM (P1/P4) file".to_string())),
+        };
+        let mut pos = 2;
+        let width = header_number(data, &mut pos)?;
+        let height = header_number(data, &mut pos)?;
+        // 先按数据长度检查文件头声明的尺寸，避免按伪造的尺寸分配内存
+        let pixels = checked_size(width, height)?;
+        let body = data.get(pos..).unwrap_or_default();
+        if raw {
+            // 文件头之后只有一个空白字符，每行按字节对齐
+            let body = body.get(1..).ok_or(ImageError::Truncated)?;
+            let stride = width.div_ceil(8);
+            if stride
+                .checked_mul(height)
+                .is_none_or(|len| body.len() < len)
+            {
+                return Err(ImageError::Truncated);
+            }
+            let mut image = Image::new(width, height);
+            for y in 0..height {
+                for x in 0..width {
+                    let byte = body[y * stride + x / 8];
+                    image.pixels[y * width + x] = byte & (0x80 >> (x % 8)) != 0;
+                }
+            }
+            Ok(image)
+        } else {
+            let bits: Vec<bool> = body
+                .iter()
+                .filter(|b| matches!(b, b'0' | b'1'))
+                .take(pixels)
+                .map(|&b| b == b'1')
+                .collect();
+            if bits.len() < pixels {
+                return Err(ImageError::Truncated);
+            }
+            Ok(Image {
+                width,
+                height,
+                pixels: bits,
+            })
+        }
+    }
+
+    /// 当前帧缓冲的内容，即屏幕上显示的画面
+    pub fn from_frame() -> Image {
+        let frame = crate::frame();
+        let mut image = Image::new(crate::WIDTH, crate::HEIGHT);
+        for (page, row) in frame.iter().enumerate() {
+            for (x, &byte) in row.iter().enumerate() {
+                for bit in 0..8 {
+                    image.set(x, page * 8 + bit, byte & (1 << bit) != 0);
+                }
+            }
+        }
+        image
+    }
+
+    /// 按文件头识别 PBM 或 PNG（需要 `png` 特性）
+    pub fn decode(data: &[u8]) -> Result<Image, ImageError> {
+        #[cfg(feature = "png")]
+        if data.starts_with(b"\x89PNG") {
+            return Image::from_png(data);
+        }
+        Image::from_pbm(data)
+    }
+
+    /// 解析 PNG 图片，亮度不低于一半的像素点亮，透明像素不点亮
+    #[cfg(feature = "png")]
+    pub fn from_png(data: &[u8]) -> Result<Image, ImageError> {
+        let mut decoder = png::Decoder::new(data);
+        decoder.set_transformations(png::Transformations::EXPAND | png::Transformations::STRIP_16);
+        let mut reader = decoder
+            .read_info()
+            .map_err(|e| ImageError::Format(e.to_string()))?;
+        let (width, height) = reader.info().size();
+        checked_size(width as usize, height as usize)?;
+        let mut buf = vec![0; reader.output_buffer_size()];
+        let info = reader.next_frame(&mut buf).map_err(|e| match e {
+            png::DecodingError::IoError(_) => ImageError::Truncated,
+            e => ImageError::Format(e.to_string()),
+        })?;
+        let channels = info.color_type.samples();
+        let mut image = Image::new(info.width as usize, info.height as usize);
+        for (y, row) in buf.chunks(info.line_size).take(image.height).enumerate() {
+            for (x, px) in row.chunks(channels).take(image.width).enumerate() {
+                let (luma, alpha) = match *px {
+                    [l] => (l as u32, 255),
+                    [l, a] => (l as u32, a),
+                    [r, g, b] => (
+                        (r as u32 * 299 + g as u32 * 587 + b as u32 * 114) / 1000,
+                        255,
+                    ),
+                    [r, g, b, a] => ((r as u32 * 299 + g as u32 * 587 + b as u32 * 114) / 1000, a),
+                    _ => (0, 0),
+                };
+                image.pixels[y * image.width + x] = luma >= 128 && alpha >= 128;
+            }
+        }
+        Ok(image)
+    }
+
+    /// 转成1位灰度的 PNG，点亮的像素为白色
+    #[cfg(feature = "png")]
+    pub fn to_png(&self) -> Vec<u8> {
+        let stride = self.width.div_ceil(8);
+        let mut data = vec![0u8; stride * self.height];
+        for y in 0..self.height {
+            for x in 0..self.width {
+                if self.pixel(x, y) {
+                    data[y * stride + x / 8] |= 0x80 >> (x % 8);
+                }
+            }
+        }
+        let mut out = Vec::new();
+        let mut encoder = png::Encoder::new(&mut out, self.width as u32, self.height as u32);
+        encoder.set_color(png::ColorType::Grayscale);
+        encoder.set_depth(png::BitDepth::One);
+        // 写入内存不会出现IO错误，尺寸和数据长度也是一致的
+        let mut writer = encoder.write_header().expect("png header");
+        writer.write_image_data(&data).expect("png data");
+        drop(writer);
+        out
+    }
+
+    /// 转成 P4 格式的 PBM
+    pub fn to_pbm(&self) -> Vec<u8> {
+        let mut out = format!("P4\n{} {}\n", self.width, self.height).into_bytes();
+        let stride = self.width.div_ceil(8);
+        for y in 0..self.height {
+            let mut row = vec![0u8; stride];
+            for x in 0..self.width {
+                if self.pixel(x, y) {
+                    row[x / 8] |= 0x80 >> (x % 8);
+                }
+            }
+            out.extend_from_slice(&row);
+        }
+        out
+    }
+
+    pub fn width(&self) -> usize {
+        self.width
+    }
+
+    pub fn height(&self) -> usize {
+        self.height
+    }
+
+    /// 坐标超出图片时返回 `false`
+    pub fn pixel(&self, x: usize, y: usize) -> bool {
+        x < self.width && y < self.height && self.pixels[y * self.width + x]
+    }
+
+    pub fn set(&mut self, x: usize, y: usize, on: bool) {
+        if x < self.width && y < self.height {
+            self.pixels[y * self.width + x] = on;
+        }
+    }
+
+    /// 把图片画到帧缓冲的 `(x, y)` 处，超出屏幕的部分被裁掉
+    pub fn draw(&self, x: u8, y: u8) {
+        for row in 0..self.height {
+            let py = y as usize + row;
+            if py >= crate::HEIGHT {
+                break;
+            }
+            for col in 0..self.width {
+                let px = x as usize + col;
+                if px >= crate::WIDTH {
+                    break;
+                }
+                setpixel(px as u8, py as u8, self.pixel(col, row));
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn pbm_round_trip() {
+        let mut image = Image::new(10, 3);
+        image.set(0, 0, true);
+        image.set(9, 2, true);
+        assert_eq!(Image::from_pbm(&image.to_pbm()), Ok(image));
+        let text = Image::from_pbm(b"P1\n# comment\n3 2\n1 0 1\n0 1 0\n").unwrap();
+        assert!(text.pixel(0, 0) && !text.pixel(1, 0) && text.pixel(1, 1));
+    }
+
+    #[test]
+    fn pbm_header_larger_than_data() {
+        for data in [
+            &b"P4\n99999999999 99999999999\n"[..],
+            b"P1\n99999999999 99999999999\n1",
+            b"P4\n4096 4096\n\0\0\0",
+            b"P1\n100 100\n1 0 1",
+        ] {
+            assert!(Image::from_pbm(data).is_err());
+        }
+    }
+}
@@ -1,21 +1,69 @@
 use embedded_hal::i2c::I2c;
 use linux_embedded_hal::I2cdev;
-mod font;
-use font::{FONT8X8,FONT16X16, FONT24X24, Font};
+use std::ops::Range;
+pub mod font;
+use font::{FontFace, MissingGlyphs};
+use text::glyph_pixel;
+mod buffer;
+mod console;
+pub mod daemon;
+mod display;
+pub mod draw;
+#[cfg(feature = "http")]
+pub mod http;
+pub mod image;
+#[cfg(feature = "log")]
+pub mod logger;
+#[cfg(feature = "mqtt")]
+pub mod mqtt;
+#[cfg(feature = "pty")]
+pub mod pty;
+pub mod region;
+pub mod text;
+#[cfg(feature = "tracing")]
+pub mod tracing;
+pub use buffer::{Buffer, Line, LineOrder, Row};
+pub use console::{AutoFlush, Console};
+pub use display::Display;
+pub use text::{HAlign, Rect, TextStyle, VAlign, measure_text};
 
 pub enum OLEDColorMode {
     ColorNormal = 0, // 正常模式 黑底白字
     ColorReserved,   // 反色模式 白底黑字
 }
 
+/// 屏幕控制芯片
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum Controller {
+    #[default]
+    Ssd1306,
+    /// 显存为132列，可见区域从第2列开始，不支持硬件滚动
+    Sh1106,
+}
+
+/// 硬件水平滚动方向
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ScrollDirection {
+    Left,
+    Right,
+}
+
 pub const HEIGHT: usize = 64; // OLED 高度
 pub const WIDTH: usize = 128; // OLED 宽度
 pub const COLUMN_SIZE: usize = 8; // 列大小(单位：bit)
 pub const PAGE: usize = HEIGHT / COLUMN_SIZE; // 页数
 
 static mut I2C_ADDR: u8 = 0x3C; // I2C 地址
+static mut CONTROLLER: Controller = Controller::Ssd1306;
 static mut FRAME_BUFFER: [[u8; WIDTH]; PAGE] = [[0; WIDTH]; PAGE];
 
+// 帧缓冲是全进程共用的，测试并行运行时要先拿到这把锁
+#[cfg(test)]
+pub(crate) fn frame_lock() -> std::sync::MutexGuard<'static, ()> {
+    static LOCK: std::sync::Mutex<()> = std::sync::Mutex::new(());
+    LOCK.lock().unwrap_or_else(|e| e.into_inner())
+}
+
 fn send(i2c: &mut I2cdev, data: u8) {
     unsafe {
         let _ = i2c.write(I2C_ADDR, &[0x40, data]);
@@ -28,11 +76,43 @@ fn sendcmd(i2c: &mut I2cdev, cmd: u8) {
     }
 }
 
+/// 设置I2C地址，默认 0x3C，有的模块为 0x3D
+pub fn set_address(addr: u8) {
+    unsafe {
+        I2C_ADDR = addr;
+    }
+}
+
+/// 设置控制芯片类型，默认 SSD1306
+pub fn set_controller(controller: Controller) {
+    unsafe {
+        CONTROLLER = controller;
+    }
+}
+
+pub fn controller() -> Controller {
+    unsafe { CONTROLLER }
+}
+
+// 设置写入的起始列，SH1106 的可见区域有2列偏移
+fn set_column(i2c: &mut I2cdev, x: u8) {
+    let x = match controller() {
+        Controller::Ssd1306 => x,
+        Controller::Sh1106 => x.saturating_add(2),
+    };
+    sendcmd(i2c, x & 0x0f);
+    sendcmd(i2c, 0x10 + ((x & 0xf0) >> 4));
+}
+
 pub fn init(i2c: &mut I2cdev) {
+    let sh1106 = controller() == Controller::Sh1106;
     sendcmd(i2c, 0xAEu8); /*关闭显示 display off*/
 
-    sendcmd(i2c, 0x20u8);
-    sendcmd(i2c, 0x10u8);
+    // SH1106 只有页寻址模式
+    if !sh1106 {
+        sendcmd(i2c, 0x20u8);
+        sendcmd(i2c, 0x10u8);
+    }
 
     sendcmd(i2c, 0xB0u8);
 
@@ -70,122 +150,81 @@ pub fn init(i2c: &mut I2cdev) {
     sendcmd(i2c, 0xDBu8);
     sendcmd(i2c, 0x20u8);
 
-    sendcmd(i2c, 0x8Du8);
-    sendcmd(i2c, 0x14u8);
+    // 电荷泵
+    if sh1106 {
+        sendcmd(i2c, 0xADu8);
+        sendcmd(i2c, 0x8Bu8);
+    } else {
+        sendcmd(i2c, 0x8Du8);
+        sendcmd(i2c, 0x14u8);
+    }
 
     sendcmd(i2c, 0xAFu8); /*开启显示 display ON*/
 }
 
 pub fn show(i2c: &mut I2cdev, x: u8, y: u8, data: u8) {
     sendcmd(i2c, 0xb0 + y);
-    sendcmd(i2c, 0x00 + (x & 0x0f));
-    sendcmd(i2c, 0x10 + ((x & 0xf0) >> 4));
+    set_column(i2c, x);
     send(i2c, data);
 }
 
 pub fn clear(i2c: &mut I2cdev) {
     for i in 0..8 {
         sendcmd(i2c, 0xb0 + i);
-        sendcmd(i2c, 0x00);
-        sendcmd(i2c, 0x10);
+        set_column(i2c, 0);
         for _j in 0..128 {
             send(i2c, 0x00);
         }
     }
 }
 
-pub struct Buffer {
-    data: Vec<String>, // 使用String代替&'a str
-    len: usize,
-    len_max: usize,
-    index: usize,
+pub fn display_buffer(buffer: &Buffer, x: u8, y: u8, font: &dyn FontFace, i2c: &mut I2cdev) {
+    newframe();
+    draw_buffer(buffer, x, y, font);
+    showframe(i2c);
 }
 
-impl Buffer {
-    pub fn new(len_max: usize) -> Self {
-        Buffer {
-            data: Vec::new(),
-            len: 0,
-            len_max: len_max,
-            index: 0,
-        }
-    }
-
-    pub fn push(&mut self, s: String) {
-        // 确保至少有一个字符串在data中
-        if self.data.is_empty() {
-            self.data.push(String::new());
-            self.len = 1;
+/// 只把缓冲区的视口画到帧缓冲中，不清屏也不刷新，用于和其它内容组合
+pub fn draw_buffer(buffer: &Buffer, x: u8, y: u8, font: &dyn FontFace) {
+    let height = font.get_font_height() as usize;
+    let mut bottom = y as usize;
+    for (i, line) in buffer.view().iter().enumerate() {
+        let row = y as usize + i * height;
+        if row >= HEIGHT {
+            break;
         }
-
-        for c in s.chars() {
-            if c == '\n' {
-                // 遇到换行符，在头部添加新行
-                self.data.insert(0, String::new());
-                self.len += 1;
-                // 保证最大行数
-                if self.len > self.len_max {
-                    self.data.pop();
-                    self.len -= 1;
-                }
-                self.index = 0; // 换行后索引重置
-            } else if c == '\r' {
-                // 遇到回车符，将索引重置为0
-                self.index = 0;
-            } else {
-                // 其他字符，添加到当前行的index位置
-                let current_line = &mut self.data[0];
-
-                // 确保当前行长度足够
-                if self.index >= current_line.chars().count() {
-                    // 填充空格直到index
-                    let fill_count = self.index - current_line.chars().count() + 1;
-                    current_line.extend(std::iter::repeat(' ').take(fill_count));
-                }
-
-                // 计算字节索引
-                let char_indices: Vec<usize> =
-                    current_line.char_indices().map(|(i, _)| i).collect();
-                if self.index < char_indices.len() {
-                    let start = char_indices[self.index];
-                    let end = if self.index + 1 < char_indices.len() {
-                        char_indices[self.index + 1]
-                    } else {
-                        current_line.len()
-                    };
-                    current_line.replace_range(start..end, &c.to_string());
-                } else {
-                    // 追加
-                    current_line.push(c);
-                }
-
-                // 索引加1
-                self.index += 1;
+        bottom = (row + height).min(HEIGHT);
+        // 按样式分段绘制，反色、加粗等来自终端的 SGR 序列
+        let mut column = x as u16;
+        for (s, style) in line.runs() {
+            if column >= WIDTH as u16 {
+                break;
             }
+            let _ = print_string_styled(column as u8, row as u8, font, s, style);
+            column += text::styled_width(font, s, style);
         }
     }
-
-    pub fn get(&self, index: usize) -> Option<&str> {
-        if index < self.len {
-            Some(&self.data[index])
-        } else {
-            None
-        }
-    }
-
-    pub fn clear(&mut self) {
-        self.data.clear();
-        self.len = 0;
-        self.index = 0;
+    if !buffer.is_live() {
+        draw_scroll_indicator(buffer, y as usize, bottom);
     }
 }
 
-pub fn display_buffer(buffer: &Buffer, x: u8, y: u8, height: u8, i2c: &mut I2cdev) {
-    newframe();
-    for (i, s) in buffer.data.iter().enumerate() {
-        print_string(x, y + (i as u8) * height, height, s);
+// 视口不在最新输出处时，在最右一列画出视口在历史中的位置
+fn draw_scroll_indicator(buffer: &Buffer, top: usize, bottom: usize) {
+    let area = bottom.saturating_sub(top);
+    let total = buffer.total_rows().max(1);
+    let len = (area * buffer.rows() / total).max(2);
+    let start = match buffer.line_order() {
+        LineOrder::NewestFirst => top + area * buffer.offset() / total,
+        // 最新一行在最下面，视口上方是更早的历史
+        LineOrder::OldestFirst => {
+            let above = total.saturating_sub(buffer.offset() + buffer.rows());
+            top + area * above / total
+        }
+    };
+    for py in start..(start + len).min(bottom) {
+        setpixel(WIDTH as u8 - 1, py as u8, true);
     }
-    showframe(i2c);
 }
 
 pub fn newframe() {
@@ -195,18 +234,41 @@ pub fn newframe() {
 }
 
 pub fn showframe(i2c: &mut I2cdev) {
-    unsafe {
-        for i in 0..PAGE {
-            sendcmd(i2c, 0xb0 + i as u8);
-            sendcmd(i2c, 0x00);
-            sendcmd(i2c, 0x10);
-            for j in 0..WIDTH {
-                send(i2c, FRAME_BUFFER[i as usize][j as usize]);
-            }
+    let frame = unsafe { FRAME_BUFFER };
+    for (i, page) in frame.iter().enumerate() {
+        sendcmd(i2c, 0xb0 + i as u8);
+        set_column(i2c, 0);
+        for &byte in page {
+            send(i2c, byte);
         }
     }
 }
 
+/// 只发送帧缓冲中一页的部分列，用于局部刷新
+pub fn showframe_span(i2c: &mut I2cdev, page: usize, columns: Range<usize>) {
+    let frame = unsafe { FRAME_BUFFER };
+    let Some(bytes) = frame.get(page).and_then(|p| p.get(columns.clone())) else {
+        return;
+    };
+    sendcmd(i2c, 0xb0 + page as u8);
+    set_column(i2c, columns.start as u8);
+    for &byte in bytes {
+        send(i2c, byte);
+    }
+}
+
+/// 当前帧缓冲的副本，按页存放，每个字节是一列中的8个像素（低位在上）
+pub fn frame() -> [[u8; WIDTH]; PAGE] {
+    unsafe { FRAME_BUFFER }
+}
+
+/// 用整帧数据替换帧缓冲
+pub fn load_frame(frame: &[[u8; WIDTH]; PAGE]) {
+    unsafe {
+        FRAME_BUFFER = *frame;
+    }
+}
+
 pub fn setpixel(x: u8, y: u8, color: bool) {
     let page = y / 8;
     let page_offset = y % 8;
@@ -224,7 +286,21 @@ pub fn setpixel(x: u8, y: u8, color: bool) {
 }
 
 // todo 将y变成像素点
-pub fn print_char(x: u8, y: u8, font: &Font, ch: char) -> Option<(u8, u8)> {
+pub fn print_char(x: u8, y: u8, font: &dyn FontFace, ch: char) -> Option<(u8, u8)> {
+    print_char_styled(x, y, font, ch, TextStyle::PLAIN)
+}
+
+/// 按样式绘制单个字符，返回 `(前进宽度, 字体高度)`。加粗会使前进宽度加1
+pub fn print_char_styled(
+    x: u8,
+    y: u8,
+    font: &dyn FontFace,
+    ch: char,
+    style: TextStyle,
+) -> Option<(u8, u8)> {
+    if !style.is_plain() {
+        return print_char_synthetic(x, y, font, ch, style);
+    }
     //返回值是字符宽度
     // let font = match height {
     //     8 => FONT8X8,
@@ -237,24 +313,38 @@ pub fn print_char(x: u8, y: u8, font: &Font, ch: char) -> Option<(u8, u8)> {
 
     let page = y / 8;
     let page_offset = y % 8;
-    let char_bytes: Option<Vec<Vec<u8>>> = font.get_char(ch);
+    let glyph = font.get_glyph(ch);
+    let metrics = font.get_metrics(ch)?;
+    // 比例字体只绘制有效列，并按左侧间距偏移
+    let left = metrics.left as usize;
+    let start = x as isize + metrics.bearing as isize;
     unsafe {
-        if let Some(char_matrix) = char_bytes {
-            for (i, row) in char_matrix.iter().enumerate() {
-                for (j, &byte) in row.iter().enumerate() {
+        if let Some(glyph) = glyph {
+            // 直接按页读取字库中的列数据，不分配内存
+            for (i, row) in glyph.pages().enumerate() {
+                for (j, &byte) in row
+                    .iter()
+                    .enumerate()
+                    .skip(left)
+                    .take(metrics.width as usize)
+                {
+                    let col = start + (j - left) as isize;
+                    if col < 0 || col as usize >= WIDTH {
+                        continue;
+                    }
+                    let col = col as usize;
                     match page_offset {
                         0 => {
-                            if (x as usize + j) < WIDTH && (page as usize + i) < PAGE {
-                                FRAME_BUFFER[page as usize + i][x as usize + j] = byte;
+                            if (page as usize + i) < PAGE {
+                                FRAME_BUFFER[page as usize + i][col] = byte;
                             }
                         }
                         _ => {
-                            if (x as usize + j) < WIDTH && (page as usize + i) < PAGE {
-                                FRAME_BUFFER[page as usize + i][x as usize + j] =
-                                    byte << page_offset;
+                            if (page as usize + i) < PAGE {
+                                FRAME_BUFFER[page as usize + i][col] = byte << page_offset;
                             }
-                            if (x as usize + j) < WIDTH && (page as usize + i + 1) < PAGE {
-                                FRAME_BUFFER[page as usize + i + 1][x as usize + j] =
+                            if (page as usize + i + 1) < PAGE {
+                                FRAME_BUFFER[page as usize + i + 1][col] =
                                     byte >> (COLUMN_SIZE as u8 - page_offset);
                             }
                         }
@@ -263,49 +353,121 @@ pub fn print_char(x: u8, y: u8, font: &Font, ch: char) -> Option<(u8, u8)> {
             }
         }
     }
-    match font.get_char_width(ch) {
-        Some(w) => {
-            return Some((w, font.get_font_height()));
+    Some((metrics.advance, font.get_font_height()))
+}
+
+// 带样式的字符逐像素合成：加粗、斜体、下划线、删除线和反色
+fn print_char_synthetic(
+    x: u8,
+    y: u8,
+    font: &dyn FontFace,
+    ch: char,
+    style: TextStyle,
+) -> Option<(u8, u8)> {
+    let metrics = font.get_metrics(ch)?;
+    let glyph = font.get_glyph(ch);
+    let h = font.get_font_height() as usize;
+    // 被跳过的字符不占位置，加粗和反色也不画
+    if metrics.advance == 0 && metrics.width == 0 {
+        return Some((0, h as u8));
+    }
+    let left = metrics.left as usize;
+    let width = metrics.width as usize;
+    let bearing = metrics.bearing as isize;
+    let bold = style.bold as usize;
+
+    let source = |col: isize, row: usize| glyph_pixel(glyph.as_ref(), left, width, col as i32, row);
+
+    let advance = metrics.advance as usize + bold;
+    let ink_end = (bearing.max(0) as usize + width + bold + style.shear(0, h.max(1))).max(advance);
+    // 反色和下划线要改写整个字符格；其它样式只点亮笔画，
+    // 不会擦掉前一个斜体字符伸进这一格的部分
+    let full = style.invert || style.underline;
+    let (first, last) = if full || style.strikethrough {
+        (0, ink_end)
+    } else {
+        (bearing.max(0) as usize, ink_end)
+    };
+
+    for row in 0..h {
+        let py = y as usize + row;
+        if py >= HEIGHT {
+            break;
         }
-        None => {
-            println!("Error: Unsupported character :{}", ch);
-            return None;
+        let shear = style.shear(row, h) as isize;
+        let line = (style.underline && row == h - 1) || (style.strikethrough && row == h / 2);
+        for col in first..last {
+            let px = x as usize + col;
+            if px >= WIDTH {
+                break;
+            }
+            let c = col as isize - bearing - shear;
+            let ink = source(c, row) || (style.bold && source(c - 1, row));
+            let on = (ink || (line && col < advance)) != style.invert;
+            if full || on {
+                setpixel(px as u8, py as u8, on);
+            }
         }
     }
+    Some((advance as u8, font.get_font_height()))
 }
 
-pub fn print_string(x: u8, y: u8, height: u8, str: &str) {
-    // unimplemented!()
-    let font = match height {
-        8 => FONT8X8,
-        16 => FONT16X16,
-        24 => FONT24X24,
-        _ => {
-            println!("Unsupported font height");
-            FONT8X8
-        }
-    };
+/// 使用指定字体输出字符串，比例字体按每个字符的宽度和字距前进。
+/// 内置字体见 `font` 模块，也可以从 `FontRegistry` 中按名称取得。
+/// 这里只做简单的逐字符折行，按词换行请使用 `text::TextBox`。
+///
+/// 字体不支持的字符会被跳过，并在返回的错误中列出；需要替换字符或方框时
+/// 使用 `font::Fallback` 包装字体。
+///
+/// 内置字体常量（如 `FONT8X8`）不含图标，要输出 `Icon` 请用 `font::with_icons`
+/// 包装字体，`FontRegistry::with_builtin` 中的字体已经包装好
+pub fn print_string(x: u8, y: u8, font: &dyn FontFace, str: &str) -> Result<(), MissingGlyphs> {
+    print_string_styled(x, y, font, str, TextStyle::PLAIN)
+}
 
+/// 按样式输出字符串，见 `text::TextStyle`
+pub fn print_string_styled(
+    x: u8,
+    y: u8,
+    font: &dyn FontFace,
+    str: &str,
+    style: TextStyle,
+) -> Result<(), MissingGlyphs> {
+    let mut missing = Vec::new();
     let mut column = x;
     let mut row = y;
+    let mut prev = None;
     // let mut page = y / 8;
     // let page_offset = y % 8;
 
     for ch in str.chars() {
-        match print_char(column, row, &font, ch) {
+        if ch.is_control() {
+            continue;
+        }
+        if let Some(p) = prev {
+            column = column.saturating_add_signed(font.get_kerning(p, ch));
+        }
+        match print_char_styled(column, row, font, ch, style) {
             Some((w, h)) => {
-                column += w;
+                prev = Some(ch);
+                column = column.saturating_add(w);
+                // 折行回到起始列，超出屏幕底部后不再绘制
                 if column >= WIDTH as u8 {
-                    column = 0;
-                    row += h;
+                    column = x;
+                    row = row.saturating_add(h);
+                    if row as usize >= HEIGHT {
+                        break;
+                    }
                 }
             }
-            None => {
-                println!("Error: Unsupported character :{}", ch);
-                return;
-            }
+            None => missing.push(ch),
         }
     }
+    if missing.is_empty() {
+        Ok(())
+    } else {
+        Err(MissingGlyphs(missing))
+    }
 }
 
 pub fn set_color_mode(i2c: &mut I2cdev, mode: OLEDColorMode) {
@@ -319,6 +481,134 @@ pub fn set_color_mode(i2c: &mut I2cdev, mode: OLEDColorMode) {
     }
 }
 
+/// 设置对比度（亮度），0-255
+pub fn set_contrast(i2c: &mut I2cdev, contrast: u8) {
+    sendcmd(i2c, 0x81);
+    sendcmd(i2c, contrast);
+}
+
+/// 打开或关闭显示，关闭后显存内容保留
+pub fn set_power(i2c: &mut I2cdev, on: bool) {
+    sendcmd(i2c, if on { 0xAF } else { 0xAE });
+}
+
+/// 开始硬件水平滚动，`start`/`end` 为页号，`interval` 为0-7的帧间隔编码
+/// （0为5帧，7为2帧）。仅 SSD1306 支持，SH1106 上什么也不做
+pub fn start_scroll(i2c: &mut I2cdev, dir: ScrollDirection, start: u8, end: u8, interval: u8) {
+    if controller() == Controller::Sh1106 {
+        return;
+    }
+    stop_scroll(i2c);
+    sendcmd(
+        i2c,
+        match dir {
+            ScrollDirection::Right => 0x26,
+            ScrollDirection::Left => 0x27,
+        },
+    );
+    sendcmd(i2c, 0x00);
+    sendcmd(i2c, start & 0x07);
+    sendcmd(i2c, interval & 0x07);
+    sendcmd(i2c, end & 0x07);
+    sendcmd(i2c, 0x00);
+    sendcmd(i2c, 0xFF);
+    sendcmd(i2c, 0x2F);
+}
+
+/// 停止硬件滚动，停止后需要重新写入显存。SH1106 上什么也不做
+pub fn stop_scroll(i2c: &mut I2cdev) {
+    if controller() == Controller::Sh1106 {
+        return;
+    }
+    sendcmd(i2c, 0x2E);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::image::Image;
+
+    #[test]
+    fn italic_keeps_previous_overhang() {
+        let _lock = frame_lock();
+        let font = &font::FONT16X16;
+        let italic = TextStyle::italic();
+        newframe();
+        print_string_styled(0, 0, font, "/", italic).unwrap();
+        let single = Image::from_frame();
+        newframe();
+        print_string_styled(0, 0, font, "/ ", italic).unwrap();
+        assert_eq!(Image::from_frame(), single);
+        newframe();
+        print_string_styled(
+            0,
+            0,
+            font,
+            "//",
+            TextStyle {
+                bold: true,
+                ..italic
+            },
+        )
+        .unwrap();
+        let both = Image::from_frame();
+        let overhang = (8..WIDTH)
+            .flat_map(|x| (0..16).map(move |y| (x, y)))
+            .filter(|&(x, y)| single.pixel(x, y));
+        assert!(overhang.clone().count() > 0);
+        assert!(overhang.into_iter().all(|(x, y)| both.pixel(x, y)));
+    }
+
+    #[test]
+    fn inverted_fills_the_cell() {
+        let _lock = frame_lock();
+        newframe();
+        print_string_styled(0, 0, &font::FONT8X8, " ", TextStyle::inverted()).unwrap();
+        let frame = Image::from_frame();
+        assert!((0..6).all(|x| (0..8).all(|y| frame.pixel(x, y))));
+    }
+
+    #[test]
+    fn scroll_indicator_shows_the_viewport() {
+        let _lock = frame_lock();
+        let mut display = crate::Display::simulated();
+        let column = |frame: &Image| -> Vec<usize> {
+            (0..HEIGHT).filter(|&y| frame.pixel(WIDTH - 1, y)).collect()
+        };
+        for order in [LineOrder::NewestFirst, LineOrder::OldestFirst] {
+            let mut buffer = Buffer::new(2).scrollback(6).order(order);
+            buffer.push("1\n2\n3\n4\n5\n6\n7\n8".to_string());
+            // 跟随最新输出时不画滚动条
+            display.display_buffer(&buffer, 0, 0, &font::FONT8X8);
+            assert!(column(&Image::from_frame()).is_empty());
+            // 8 行中显示最旧的 2 行，两行文字高 16 像素，滚动条长 4
+            buffer.scroll_home();
+            display.display_buffer(&buffer, 0, 0, &font::FONT8X8);
+            let expected: Vec<usize> = match order {
+                LineOrder::NewestFirst => (12..16).collect(),
+                LineOrder::OldestFirst => (0..4).collect(),
+            };
+            assert_eq!(column(&Image::from_frame()), expected);
+        }
+    }
+
+    #[test]
+    fn print_string_wraps_to_its_start_column() {
+        let _lock = frame_lock();
+        newframe();
+        // 从第 100 列起每行放 5 个字符，第 6 个回到第 100 列
+        print_string(100, 0, &font::FONT8X8, "IIIIII").unwrap();
+        let frame = Image::from_frame();
+        let lit = |xs: std::ops::Range<usize>, ys: std::ops::Range<usize>| {
+            xs.flat_map(|x| ys.clone().map(move |y| (x, y)))
+                .any(|(x, y)| frame.pixel(x, y))
+        };
+        assert!(lit(100..106, 8..16));
+        assert!(!lit(0..100, 0..16));
+        assert!(!lit(106..WIDTH, 8..16));
+    }
+}
+
 // #[cfg(test)]
 // mod tests {
 //     use super::*;
@@ -349,7 +639,7 @@ pub fn set_color_mode(i2c: &mut I2cdev, mode: OLEDColorMode) {
 
 //         // 只在时间发生变化时才更新显示
 //         if time_str != last_time_str {
-//             oled::print_string( col,row, 24, &time_str);
+//             oled::print_string(col, row, &oled::font::FONT24X24, &time_str);
 
 //             oled::showframe(&mut i2c);
 //             oled::newframe();
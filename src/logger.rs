@@ -0,0 +1,246 @@
+use crate::font::Icon;
+use crate::{AutoFlush, Console};
+use log::{Level, LevelFilter, Log, Metadata, Record, SetLoggerError};
+use std::fmt::Write;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+
+/// 每行日志前的时间
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum Timestamp {
+    /// 不显示时间
+    #[default]
+    Off,
+    /// 日志器创建以来的秒数
+    Uptime,
+    /// UTC 时间 `HH:MM:SS`
+    Utc,
+}
+
+/// 把 `log` 日志写到屏幕控制台的日志器。
+///
+/// 每行前面是级别图标（字体没有图标时用 `E/W/I/D/T`，用 `font::with_icons` 给字体接上图标），
+/// 可选时间和目标模块。
+/// 写日志只更新缓冲区，由后台线程按 `refresh_interval` 刷新屏幕，避免占满I2C总线
+pub struct OledLogger {
+    console: Arc<Mutex<Console>>,
+    level: LevelFilter,
+    targets: Vec<(String, LevelFilter)>,
+    timestamp: Timestamp,
+    show_target: bool,
+    interval: Duration,
+    dirty: Arc<AtomicBool>,
+    start: Instant,
+}
+
+impl OledLogger {
+    /// 默认记录 `Warn` 及以上级别，每500毫秒最多刷新一次
+    pub fn new(console: Console) -> Self {
+        OledLogger {
+            // 刷新由本对象控制
+            console: Arc::new(Mutex::new(console.auto_flush(AutoFlush::Never))),
+            level: LevelFilter::Warn,
+            targets: Vec::new(),
+            timestamp: Timestamp::Off,
+            show_target: false,
+            interval: Duration::from_millis(500),
+            dirty: Arc::new(AtomicBool::new(false)),
+            start: Instant::now(),
+        }
+    }
+
+    pub fn level(mut self, level: LevelFilter) -> Self {
+        self.level = level;
+        self
+    }
+
+    /// 单独设置某个模块（及其子模块）的级别，最长的前缀优先
+    pub fn target_level(mut self, target: &str, level: LevelFilter) -> Self {
+        self.targets.push((target.to_string(), level));
+        self.targets
+            .sort_by_key(|(t, _)| std::cmp::Reverse(t.len()));
+        self
+    }
+
+    pub fn timestamp(mut self, timestamp: Timestamp) -> Self {
+        self.timestamp = timestamp;
+        self
+    }
+
+    /// 是否在每行显示目标模块
+    pub fn show_target(mut self, show: bool) -> Self {
+        self.show_target = show;
+        self
+    }
+
+    /// 两次刷新屏幕的最短间隔
+    pub fn refresh_interval(mut self, interval: Duration) -> Self {
+        self.interval = interval;
+        self
+    }
+
+    /// 共享的控制台，应用也可以通过它写入或翻看历史
+    pub fn console(&self) -> Arc<Mutex<Console>> {
+        self.console.clone()
+    }
+
+    /// 安装为全局日志器，并启动刷新线程
+    pub fn init(self) -> Result<(), SetLoggerError> {
+        let max = self
+            .targets
+            .iter()
+            .map(|(_, l)| *l)
+            .fold(self.level, std::cmp::max);
+        let console = self.console.clone();
+        let dirty = self.dirty.clone();
+        let interval = self.interval;
+        log::set_boxed_logger(Box::new(self))?;
+        log::set_max_level(max);
+        std::thread::spawn(move || {
+            loop {
+                std::thread::sleep(interval);
+                if dirty.swap(false, Ordering::AcqRel)
+                    && let Ok(mut console) = console.lock()
+                {
+                    console.refresh();
+                }
+            }
+        });
+        Ok(())
+    }
+
+    fn level_for(&self, target: &str) -> LevelFilter {
+        self.targets
+            .iter()
+            .find(|(t, _)| {
+                target == t
+                    || (target.starts_with(t.as_str()) && target[t.len()..].starts_with("::"))
+            })
+            .map_or(self.level, |(_, l)| *l)
+    }
+
+    // 级别图标，字体不支持图标时用字母代替
+    fn indicator(console: &Console, level: Level) -> char {
+        let icon = match level {
+            Level::Error => Icon::Cross,
+            Level::Warn => Icon::Warning,
+            Level::Info => Icon::Info,
+            Level::Debug => Icon::ArrowRight,
+            Level::Trace => Icon::ArrowDown,
+        };
+        if console.font().get_metrics(icon.char()).is_some() {
+            icon.char()
+        } else {
+            level.as_str().chars().next().unwrap_or('?')
+        }
+    }
+}
+
+impl Log for OledLogger {
+    fn enabled(&self, metadata: &Metadata) -> bool {
+        metadata.level() <= self.level_for(metadata.target())
+    }
+
+    fn log(&self, record: &Record) {
+        if !self.enabled(record.metadata()) {
+            return;
+        }
+        let Ok(mut console) = self.console.lock() else {
+            return;
+        };
+        let mut line = String::new();
+        line.push(OledLogger::indicator(&console, record.level()));
+        match self.timestamp {
+            Timestamp::Off => {}
+            Timestamp::Uptime => {
+                let _ = write!(line, " {:.1}", self.start.elapsed().as_secs_f32());
+            }
+            Timestamp::Utc => {
+                let secs = SystemTime::now()
+                    .duration_since(UNIX_EPOCH)
+                    .map_or(0, |d| d.as_secs())
+                    % 86400;
+                let _ = write!(
+                    line,
+                    " {:02}:{:02}:{:02}",
+                    secs / 3600,
+                    secs / 60 % 60,
+                    secs % 60
+                );
+            }
+        }
+        if self.show_target {
+            let _ = write!(line, " {}:", record.target());
+        }
+        let _ = writeln!(line, " {}", record.args());
+        console.write_text(&line);
+        self.dirty.store(true, Ordering::Release);
+    }
+
+    fn flush(&self) {
+        if let Ok(mut console) = self.console.lock() {
+            console.refresh();
+            self.dirty.store(false, Ordering::Release);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Display;
+    use crate::font::{FONT8X8, SharedFont, with_icons};
+
+    fn logger(font: SharedFont) -> OledLogger {
+        OledLogger::new(Console::new(Display::simulated(), font))
+    }
+
+    #[test]
+    fn target_levels_match_whole_modules() {
+        // 后加入的短前缀不会盖过更长的前缀
+        let logger = logger(Arc::new(FONT8X8))
+            .target_level("foo::bar", LevelFilter::Error)
+            .target_level("foo", LevelFilter::Debug);
+        assert_eq!(logger.level_for("foo"), LevelFilter::Debug);
+        assert_eq!(logger.level_for("foo::baz"), LevelFilter::Debug);
+        assert_eq!(logger.level_for("foo::bar"), LevelFilter::Error);
+        assert_eq!(logger.level_for("foo::bar::baz"), LevelFilter::Error);
+        assert_eq!(logger.level_for("foobar"), LevelFilter::Warn);
+        assert_eq!(logger.level_for("foo::barbaz"), LevelFilter::Debug);
+        assert_eq!(logger.level_for("other"), LevelFilter::Warn);
+    }
+
+    #[test]
+    fn indicator_falls_back_to_letters() {
+        let plain = Console::new(Display::simulated(), Arc::new(FONT8X8));
+        let letters: String = Level::iter()
+            .map(|l| OledLogger::indicator(&plain, l))
+            .collect();
+        assert_eq!(letters, "EWIDT");
+
+        let icons = Console::new(Display::simulated(), with_icons(Arc::new(FONT8X8)));
+        assert_eq!(
+            OledLogger::indicator(&icons, Level::Warn),
+            Icon::Warning.char()
+        );
+    }
+
+    #[test]
+    fn records_below_the_level_are_dropped() {
+        let logger = logger(Arc::new(FONT8X8));
+        let record = |level| {
+            Record::builder()
+                .level(level)
+                .target("app")
+                .args(format_args!("disk full"))
+                .build()
+        };
+        logger.log(&record(Level::Info));
+        logger.log(&record(Level::Warn));
+        let console = logger.console();
+        let console = console.lock().unwrap();
+        assert_eq!(console.buffer().get(0), Some("W disk full"));
+        assert_eq!(console.buffer().get(1), Some(""));
+    }
+}
@@ -0,0 +1,457 @@
+//! MQTT 客户端：订阅代理上的主题来更新屏幕，并发布屏幕状态
+//!
+//! 默认主题（前缀 `oled` 可以修改）：
+//!
+//! ```text
+//! oled/text/区域    订阅，消息是区域中显示的文字，也可以是 {"text", "font", "align", "valign"}
+//! oled/frame        订阅，整屏的 PBM（开启 png 特性时也可以是 PNG）
+//! oled/command      订阅，消息按 daemon 的行协议执行，可以包含多条请求
+//! oled/status       发布，online / offline（遗嘱消息），保留
+//! oled/state        发布，对比度、显示开关、区域和运行时间的 JSON，保留
+//! oled/error        发布，处理失败的原因
+//! ```
+
+use crate::daemon::{self, Request, Screen, Server};
+use crate::{HAlign, VAlign};
+use rumqttc::{Client, Event, LastWill, MqttOptions, Packet, QoS};
+use serde_json::Value;
+use std::io::Cursor;
+use std::sync::Mutex;
+use std::thread;
+use std::time::{Duration, Instant};
+
+/// 连接 MQTT 代理的桥接器
+pub struct MqttBridge {
+    options: MqttOptions,
+    text: String,
+    frame: String,
+    command: String,
+    status: String,
+    state: String,
+    error: String,
+    interval: Duration,
+    started: Instant,
+}
+
+impl MqttBridge {
+    /// 连接 `host:port` 上的代理，客户端 ID 为 `oled-进程号`
+    pub fn new(host: &str, port: u16) -> Self {
+        let mut options = MqttOptions::new(format!("oled-{}", std::process::id()), host, port);
+        options.set_keep_alive(Duration::from_secs(30));
+        // 整屏的 PNG 可能比默认的 10KB 大
+        options.set_max_packet_size(1 << 20, 1 << 20);
+        MqttBridge::with_options(options)
+    }
+
+    /// 使用自定义的连接选项，例如客户端 ID、用户名和密码
+    pub fn with_options(options: MqttOptions) -> Self {
+        MqttBridge {
+            options,
+            text: String::new(),
+            frame: String::new(),
+            command: String::new(),
+            status: String::new(),
+            state: String::new(),
+            error: String::new(),
+            interval: Duration::from_secs(60),
+            started: Instant::now(),
+        }
+        .prefix("oled")
+    }
+
+    /// 把所有主题放到 `prefix` 下，之前单独设置的主题会被覆盖
+    pub fn prefix(mut self, prefix: &str) -> Self {
+        self.text = format!("{}/text", prefix);
+        self.frame = format!("{}/frame", prefix);
+        self.command = format!("{}/command", prefix);
+        self.status = format!("{}/status", prefix);
+        self.state = format!("{}/state", prefix);
+        self.error = format!("{}/error", prefix);
+        self
+    }
+
+    /// 文字主题的前缀，订阅 `topic/+`，最后一级是区域名称
+    pub fn text_topic(mut self, topic: &str) -> Self {
+        self.text = topic.to_string();
+        self
+    }
+
+    pub fn frame_topic(mut self, topic: &str) -> Self {
+        self.frame = topic.to_string();
+        self
+    }
+
+    pub fn command_topic(mut self, topic: &str) -> Self {
+        self.command = topic.to_string();
+        self
+    }
+
+    /// 发布 online / offline 的主题
+    pub fn status_topic(mut self, topic: &str) -> Self {
+        self.status = topic.to_string();
+        self
+    }
+
+    pub fn state_topic(mut self, topic: &str) -> Self {
+        self.state = topic.to_string();
+        self
+    }
+
+    pub fn error_topic(mut self, topic: &str) -> Self {
+        self.error = topic.to_string();
+        self
+    }
+
+    /// 定时发布状态的间隔，默认60秒；处理完每条消息后也会发布
+    pub fn state_interval(mut self, interval: Duration) -> Self {
+        self.interval = interval;
+        self
+    }
+
+    /// 处理一条收到的消息。不需要连接代理，可以直接用来测试或接入其它消息来源
+    pub fn handle(
+        &self,
+        screen: &Mutex<Screen>,
+        topic: &str,
+        payload: &[u8],
+    ) -> Result<(), String> {
+        let request = if let Some(region) = topic
+            .strip_prefix(self.text.as_str())
+            .and_then(|rest| rest.strip_prefix('/'))
+        {
+            text_request(region, payload)?
+        } else if topic == self.frame {
+            Request::Frame(payload.to_vec())
+        } else if topic == self.command {
+            let mut replies = Vec::new();
+            daemon::process(&mut Cursor::new(payload), &mut replies, screen)
+                .map_err(|e| e.to_string())?;
+            return command_result(&replies);
+        } else {
+            return Err(format!("unexpected topic {}", topic));
+        };
+        daemon::execute(screen, request)
+    }
+
+    /// 发布到状态主题的 JSON
+    pub fn state(&self, screen: &Mutex<Screen>) -> Vec<u8> {
+        state(screen, self.started)
+    }
+
+    /// 连接代理并处理消息，断线后自动重连，不会返回
+    pub fn run(self, server: &Server) {
+        server.start_refresh();
+        let screen = server.screen();
+        let mut options = self.options.clone();
+        options.set_last_will(LastWill::new(
+            &self.status,
+            "offline",
+            QoS::AtLeastOnce,
+            true,
+        ));
+        let (client, mut connection) = Client::new(options, 64);
+
+        // 定时发布状态，代理和订阅者可以据此判断屏幕是否正常
+        let heartbeat = client.clone();
+        let topic = self.state.clone();
+        let interval = self.interval;
+        let started = self.started;
+        let state_screen = screen.clone();
+        thread::spawn(move || {
+            loop {
+                thread::sleep(interval);
+                let payload = state(&state_screen, started);
+                let _ = heartbeat.try_publish(&topic, QoS::AtMostOnce, true, payload);
+            }
+        });
+
+        for event in connection.iter() {
+            match event {
+                // 每次连接（包括重连）后重新订阅
+                Ok(Event::Incoming(Packet::ConnAck(_))) => {
+                    let _ = client.try_subscribe(format!("{}/+", self.text), QoS::AtLeastOnce);
+                    let _ = client.try_subscribe(&self.frame, QoS::AtLeastOnce);
+                    let _ = client.try_subscribe(&self.command, QoS::AtLeastOnce);
+                    let _ = client.try_publish(&self.status, QoS::AtLeastOnce, true, "online");
+                    let _ =
+                        client.try_publish(&self.state, QoS::AtMostOnce, true, self.state(&screen));
+                }
+                Ok(Event::Incoming(Packet::Publish(publish))) => {
+                    if let Err(e) = self.handle(&screen, &publish.topic, &publish.payload) {
+                        let _ = client.try_publish(&self.error, QoS::AtMostOnce, false, e);
+                    }
+                    let _ =
+                        client.try_publish(&self.state, QoS::AtMostOnce, true, self.state(&screen));
+                }
+                Ok(_) => {}
+                // 连接失败时稍等再重连
+                Err(_) => thread::sleep(Duration::from_secs(1)),
+            }
+        }
+    }
+}
+
+// 命令消息中失败的请求合并为一条错误
+fn command_result(replies: &[u8]) -> Result<(), String> {
+    let errors: Vec<String> = String::from_utf8_lossy(replies)
+        .lines()
+        .filter_map(|line| line.strip_prefix("err "))
+        .map(str::to_string)
+        .collect();
+    if errors.is_empty() {
+        Ok(())
+    } else {
+        Err(errors.join("; "))
+    }
+}
+
+fn state(screen: &Mutex<Screen>, started: Instant) -> Vec<u8> {
+    let mut state = screen
+        .lock()
+        .map(|screen| screen.state())
+        .unwrap_or_else(|_| Value::Object(Default::default()));
+    if let Value::Object(map) = &mut state {
+        map.insert("uptime".to_string(), started.elapsed().as_secs().into());
+    }
+    state.to_string().into_bytes()
+}
+
+// 纯文字，或包含字体和对齐方式的 JSON 对象
+fn text_request(region: &str, payload: &[u8]) -> Result<Request, String> {
+    let mut text = std::str::from_utf8(payload)
+        .map_err(|e| e.to_string())?
+        .to_string();
+    let mut font = "8x8".to_string();
+    let mut align = HAlign::Left;
+    let mut valign = VAlign::Top;
+    if let Ok(Value::Object(fields)) = serde_json::from_str::<Value>(&text) {
+        let field = |name: &str| fields.get(name).and_then(Value::as_str);
+        if let Some(f) = field("font") {
+            font = f.to_string();
+        }
+        if let Some(a) = field("align") {
+            align = a.parse()?;
+        }
+        if let Some(v) = field("valign") {
+            valign = v.parse()?;
+        }
+        text = field("text").unwrap_or_default().to_string();
+    }
+    Ok(Request::Text {
+        region: region.to_string(),
+        font,
+        align,
+        valign,
+        text,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::image::Image;
+    use crate::{Display, HEIGHT, WIDTH, frame_lock};
+    use std::io::{Read, Write};
+    use std::net::{TcpListener, TcpStream};
+
+    fn screen() -> Mutex<Screen> {
+        Mutex::new(Screen::new(Display::simulated()))
+    }
+
+    fn blank() -> Image {
+        Image::new(WIDTH, HEIGHT)
+    }
+
+    #[test]
+    fn text_topics() {
+        let _lock = frame_lock();
+        let screen = screen();
+        let bridge = MqttBridge::new("localhost", 1883).prefix("t");
+        bridge.handle(&screen, "t/text/screen", b"Hello").unwrap();
+        let plain = Image::from_frame();
+        assert_ne!(plain, blank());
+
+        let json = br#"{"text": "Hello", "align": "right", "valign": "bottom"}"#;
+        bridge.handle(&screen, "t/text/screen", json).unwrap();
+        let aligned = Image::from_frame();
+        assert_ne!(aligned, plain);
+        assert!(!(0..8).any(|y| (0..WIDTH).any(|x| aligned.pixel(x, y))));
+
+        assert_eq!(
+            bridge.handle(&screen, "t/text/nope", b"x"),
+            Err("unknown region nope".to_string())
+        );
+        assert!(
+            bridge
+                .handle(&screen, "t/text/screen", br#"{"align": "up"}"#)
+                .is_err()
+        );
+        assert!(bridge.handle(&screen, "t/other", b"x").is_err());
+    }
+
+    #[test]
+    fn frame_topic() {
+        let _lock = frame_lock();
+        let screen = screen();
+        let bridge = MqttBridge::new("localhost", 1883).frame_topic("display/frame");
+        let mut image = blank();
+        image.set(7, 9, true);
+        bridge
+            .handle(&screen, "display/frame", &image.to_pbm())
+            .unwrap();
+        assert_eq!(Image::from_frame(), image);
+        assert!(
+            bridge
+                .handle(&screen, "display/frame", b"P4\n99999999 9999\n")
+                .is_err()
+        );
+        assert!(
+            bridge
+                .handle(&screen, "oled/frame", &image.to_pbm())
+                .is_err()
+        );
+    }
+
+    #[test]
+    fn command_topic() {
+        let _lock = frame_lock();
+        let screen = screen();
+        let bridge = MqttBridge::new("localhost", 1883);
+        bridge
+            .handle(&screen, "oled/command", b"pixel screen 2 3\ncontrast 10\n")
+            .unwrap();
+        assert!(Image::from_frame().pixel(2, 3));
+        assert_eq!(
+            bridge.handle(
+                &screen,
+                "oled/command",
+                b"bogus\npixel screen 4 4\npixel nope 0 0"
+            ),
+            Err("unknown command bogus; unknown region nope".to_string())
+        );
+        assert!(Image::from_frame().pixel(4, 4));
+    }
+
+    #[test]
+    fn command_errors_are_joined() {
+        assert_eq!(command_result(b"ok\nok\n"), Ok(()));
+        assert_eq!(command_result(b""), Ok(()));
+        assert_eq!(
+            command_result(b"err a\nok\nerr b c\n"),
+            Err("a; b c".to_string())
+        );
+    }
+
+    #[test]
+    fn state_json() {
+        let _lock = frame_lock();
+        let screen = screen();
+        let bridge = MqttBridge::new("localhost", 1883);
+        bridge
+            .handle(
+                &screen,
+                "oled/command",
+                b"contrast 7\npower off\nregion bar 0 0 128 8",
+            )
+            .unwrap();
+        let state: Value = serde_json::from_slice(&bridge.state(&screen)).unwrap();
+        assert_eq!(state["contrast"], 7);
+        assert_eq!(state["power"], false);
+        assert_eq!(state["inverted"], false);
+        assert_eq!(state["regions"]["bar"], serde_json::json!([0, 0, 128, 8]));
+        assert_eq!(state["uptime"], 0);
+    }
+
+    // 最简单的 MQTT 3.1.1 代理替身：只和一个客户端通信
+    struct Broker(TcpStream);
+
+    impl Broker {
+        fn read(&mut self) -> (u8, Vec<u8>) {
+            let mut header = [0; 1];
+            self.0.read_exact(&mut header).unwrap();
+            let (mut len, mut shift) = (0usize, 0);
+            loop {
+                let mut byte = [0; 1];
+                self.0.read_exact(&mut byte).unwrap();
+                len |= ((byte[0] & 0x7f) as usize) << shift;
+                shift += 7;
+                if byte[0] & 0x80 == 0 {
+                    break;
+                }
+            }
+            let mut body = vec![0; len];
+            self.0.read_exact(&mut body).unwrap();
+            (header[0], body)
+        }
+
+        fn write(&mut self, header: u8, body: &[u8]) {
+            assert!(body.len() < 128);
+            self.0.write_all(&[header, body.len() as u8]).unwrap();
+            self.0.write_all(body).unwrap();
+        }
+
+        fn publish(&mut self, topic: &str, payload: &[u8]) {
+            let mut body = (topic.len() as u16).to_be_bytes().to_vec();
+            body.extend_from_slice(topic.as_bytes());
+            body.extend_from_slice(payload);
+            self.write(0x30, &body);
+        }
+
+        // 应答客户端的报文，返回客户端发布的下一条消息
+        fn next_publish(&mut self) -> (String, String) {
+            loop {
+                let (header, body) = self.read();
+                match header >> 4 {
+                    1 => self.write(0x20, &[0, 0]),
+                    3 => {
+                        let len = u16::from_be_bytes([body[0], body[1]]) as usize;
+                        let topic = String::from_utf8(body[2..2 + len].to_vec()).unwrap();
+                        let mut payload = &body[2 + len..];
+                        if header & 0x06 != 0 {
+                            self.write(0x40, &payload[..2]);
+                            payload = &payload[2..];
+                        }
+                        return (topic, String::from_utf8_lossy(payload).into_owned());
+                    }
+                    8 => self.write(0x90, &[body[0], body[1], 1]),
+                    12 => self.write(0xd0, &[]),
+                    _ => {}
+                }
+            }
+        }
+
+        fn expect(&mut self, topic: &str) -> String {
+            loop {
+                let (t, payload) = self.next_publish();
+                if t == topic {
+                    return payload;
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn bridge_with_broker_stand_in() {
+        let _lock = frame_lock();
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let port = listener.local_addr().unwrap().port();
+        let server = Server::new(Display::simulated());
+        thread::spawn(move || {
+            MqttBridge::new("127.0.0.1", port)
+                .prefix("test")
+                .run(&server)
+        });
+        let mut broker = Broker(listener.accept().unwrap().0);
+
+        assert_eq!(broker.expect("test/status"), "online");
+        broker.expect("test/state");
+        broker.publish("test/command", b"rect screen 0 0 128 64");
+        let state: Value = serde_json::from_str(&broker.expect("test/state")).unwrap();
+        assert_eq!(state["simulated"], true);
+        let frame = Image::from_frame();
+        assert!(frame.pixel(0, 0) && frame.pixel(127, 63) && !frame.pixel(1, 1));
+
+        broker.publish("test/text/nope", b"x");
+        assert_eq!(broker.expect("test/error"), "unknown region nope");
+    }
+}
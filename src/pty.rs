@@ -0,0 +1,145 @@
+use crate::font::FontFace;
+use crate::{HEIGHT, WIDTH};
+use std::fs::File;
+use std::io::{self, Read, Write};
+use std::os::fd::{AsRawFd, FromRawFd, OwnedFd};
+use std::os::unix::process::CommandExt;
+use std::process::{Child, Command, Stdio};
+use std::ptr;
+
+/// 用指定字体时屏幕能容纳的终端大小 `(行, 列)`，列数按 `'M'` 的宽度计算
+pub fn terminal_size(font: &dyn FontFace) -> (u16, u16) {
+    let h = font.get_font_height().max(1) as usize;
+    let w = font
+        .get_metrics('M')
+        .map_or(h / 2, |m| m.advance as usize)
+        .max(1);
+    ((HEIGHT / h).max(1) as u16, (WIDTH / w).max(1) as u16)
+}
+
+fn winsize(rows: u16, cols: u16) -> libc::winsize {
+    libc::winsize {
+        ws_row: rows,
+        ws_col: cols,
+        ws_xpixel: WIDTH as u16,
+        ws_ypixel: HEIGHT as u16,
+    }
+}
+
+/// 伪终端中运行的子进程。读取得到程序的输出（含转义序列），写入则作为程序的键盘输入
+pub struct Pty {
+    master: File,
+    child: Child,
+}
+
+impl Pty {
+    /// 在 `rows` 行 `cols` 列的伪终端中启动程序，未设置 `TERM` 时使用 `vt100`，
+    /// 它用到的滚动区域、光标保存等序列 `Buffer` 都支持
+    pub fn spawn(mut command: Command, rows: u16, cols: u16) -> io::Result<Pty> {
+        let mut master = -1;
+        let mut slave = -1;
+        let size = winsize(rows, cols);
+        let ret =
+            unsafe { libc::openpty(&mut master, &mut slave, ptr::null_mut(), ptr::null(), &size) };
+        if ret < 0 {
+            return Err(io::Error::last_os_error());
+        }
+        let master = unsafe { File::from_raw_fd(master) };
+        let slave = unsafe { OwnedFd::from_raw_fd(slave) };
+        // 主端不能被子进程继承，否则子进程退出后读不到结束
+        unsafe { libc::fcntl(master.as_raw_fd(), libc::F_SETFD, libc::FD_CLOEXEC) };
+
+        if !command.get_envs().any(|(k, _)| k == "TERM") {
+            command.env("TERM", "vt100");
+        }
+        command
+            .stdin(Stdio::from(slave.try_clone()?))
+            .stdout(Stdio::from(slave.try_clone()?))
+            .stderr(Stdio::from(slave));
+        // 新建会话并把伪终端设为控制终端，程序才能收到 SIGWINCH 和 Ctrl-C
+        unsafe {
+            command.pre_exec(|| {
+                if libc::setsid() < 0 || libc::ioctl(0, libc::TIOCSCTTY, 0) < 0 {
+                    return Err(io::Error::last_os_error());
+                }
+                Ok(())
+            });
+        }
+        let child = command.spawn()?;
+        Ok(Pty { master, child })
+    }
+
+    /// 改变终端大小，内核会向程序发送 SIGWINCH
+    pub fn resize(&self, rows: u16, cols: u16) -> io::Result<()> {
+        let size = winsize(rows, cols);
+        if unsafe { libc::ioctl(self.master.as_raw_fd(), libc::TIOCSWINSZ, &size) } < 0 {
+            return Err(io::Error::last_os_error());
+        }
+        Ok(())
+    }
+
+    /// 复制一个主端句柄，用于在另一个线程中读取输出或写入输入
+    pub fn try_clone_master(&self) -> io::Result<File> {
+        self.master.try_clone()
+    }
+
+    pub fn child(&mut self) -> &mut Child {
+        &mut self.child
+    }
+}
+
+impl Read for Pty {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        self.master.read(buf)
+    }
+}
+
+impl Write for Pty {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.master.write(buf)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.master.flush()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Buffer;
+
+    // 读到程序退出（伪终端在从端全部关闭后返回 EIO）
+    fn run(mut pty: Pty, rows: u16, cols: u16) -> Buffer {
+        let mut output = Vec::new();
+        let mut buf = [0; 256];
+        while let Ok(n @ 1..) = pty.read(&mut buf) {
+            output.extend_from_slice(&buf[..n]);
+        }
+        pty.child().wait().unwrap();
+        let mut buffer = Buffer::terminal(rows as usize).columns(cols as usize);
+        buffer.push(String::from_utf8_lossy(&output).into_owned());
+        buffer
+    }
+
+    #[test]
+    fn output_and_window_size() {
+        let mut command = Command::new("sh");
+        command.args(["-c", r"printf 'a\033[2;3Hb\n'; stty size"]);
+        let pty = Pty::spawn(command, 4, 20).unwrap();
+        let buffer = run(pty, 4, 20);
+        let lines: Vec<_> = (0..buffer.len()).filter_map(|i| buffer.get(i)).collect();
+        assert_eq!(lines, ["a", "  b", "4 20", ""]);
+    }
+
+    #[test]
+    fn resize_reaches_the_program() {
+        let mut command = Command::new("sh");
+        command.args(["-c", "stty -echo; read x; stty size"]);
+        let mut pty = Pty::spawn(command, 4, 20).unwrap();
+        pty.resize(3, 10).unwrap();
+        pty.write_all(b"\n").unwrap();
+        let buffer = run(pty, 3, 10);
+        assert!((0..buffer.len()).any(|i| buffer.get(i) == Some("3 10")));
+    }
+}
@@ -0,0 +1,430 @@
+//! 把屏幕分成命名的矩形区域（如状态栏、主区域、页脚），各个组件只在自己的区域内绘制，
+//! 刷新时只发送改变过的部分
+
+use crate::font::{FontFace, FontRegistry, MissingGlyphs};
+use crate::image::Image;
+use crate::text::{self, HAlign, TextBox, TextLayout, VAlign};
+use crate::{Buffer, Display, HEIGHT, PAGE, Rect, WIDTH, draw};
+use std::collections::BTreeMap;
+use std::fmt;
+use std::ops::Range;
+use std::sync::{Arc, Mutex, MutexGuard};
+
+/// 区域操作错误
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum RegionError {
+    /// 没有这个名称的区域
+    Unknown(String),
+    /// 区域已被其它组件占用
+    Claimed(String),
+}
+
+impl fmt::Display for RegionError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            RegionError::Unknown(name) => write!(f, "unknown region {}", name),
+            RegionError::Claimed(name) => write!(f, "region {} is already claimed", name),
+        }
+    }
+}
+
+impl std::error::Error for RegionError {}
+
+struct Entry {
+    rect: Rect,
+    claimed: bool,
+}
+
+/// 区域中的绘制上下文，坐标相对于区域左上角，超出区域的部分在绘制结束后被裁掉
+pub struct Canvas<'a> {
+    rect: Rect,
+    fonts: &'a FontRegistry,
+}
+
+impl Canvas<'_> {
+    /// 区域在屏幕上的位置
+    pub fn bounds(&self) -> Rect {
+        self.rect
+    }
+
+    pub fn width(&self) -> u8 {
+        self.rect.w
+    }
+
+    pub fn height(&self) -> u8 {
+        self.rect.h
+    }
+
+    /// 显示屏的字体表
+    pub fn fonts(&self) -> &FontRegistry {
+        self.fonts
+    }
+
+    fn at(&self, x: i32, y: i32) -> (i32, i32) {
+        (
+            x.saturating_add(self.rect.x as i32),
+            y.saturating_add(self.rect.y as i32),
+        )
+    }
+
+    // 文字绘制函数只接受屏幕内的坐标
+    fn at_u8(&self, x: u8, y: u8) -> (u8, u8) {
+        (self.rect.x.saturating_add(x), self.rect.y.saturating_add(y))
+    }
+
+    /// 用指定颜色填满整个区域
+    pub fn fill(&mut self, color: bool) {
+        let r = self.rect;
+        draw::fill_rect(r.x as i32, r.y as i32, r.w as i32, r.h as i32, color);
+    }
+
+    pub fn clear(&mut self) {
+        self.fill(false);
+    }
+
+    pub fn pixel(&mut self, x: i32, y: i32, color: bool) {
+        let (x, y) = self.at(x, y);
+        draw::pixel(x, y, color);
+    }
+
+    pub fn line(&mut self, x0: i32, y0: i32, x1: i32, y1: i32, color: bool) {
+        let (x0, y0) = self.at(x0, y0);
+        let (x1, y1) = self.at(x1, y1);
+        draw::line(x0, y0, x1, y1, color);
+    }
+
+    pub fn rect(&mut self, x: i32, y: i32, w: i32, h: i32, color: bool) {
+        let (x, y) = self.at(x, y);
+        draw::rect(x, y, w, h, color);
+    }
+
+    pub fn fill_rect(&mut self, x: i32, y: i32, w: i32, h: i32, color: bool) {
+        let (x, y) = self.at(x, y);
+        draw::fill_rect(x, y, w, h, color);
+    }
+
+    pub fn circle(&mut self, x: i32, y: i32, r: i32, color: bool) {
+        let (x, y) = self.at(x, y);
+        draw::circle(x, y, r, color);
+    }
+
+    pub fn text(
+        &mut self,
+        x: u8,
+        y: u8,
+        font: &dyn FontFace,
+        s: &str,
+    ) -> Result<(), MissingGlyphs> {
+        let (x, y) = self.at_u8(x, y);
+        crate::print_string(x, y, font, s)
+    }
+
+    /// 在整个区域内对齐绘制文字
+    pub fn text_aligned(&mut self, font: &dyn FontFace, s: &str, h: HAlign, v: VAlign) {
+        text::draw_text_aligned(self.rect, font, s, h, v);
+    }
+
+    /// 在整个区域内按词换行绘制文字，放不下时显示省略号
+    pub fn text_box(&mut self, font: &dyn FontFace, s: &str) -> TextLayout {
+        TextBox::new(self.rect, font).ellipsis(true).draw(s)
+    }
+
+    pub fn image(&mut self, x: i32, y: i32, image: &Image) {
+        let (x, y) = self.at(x, y);
+        draw::image(x, y, image);
+    }
+
+    /// 在区域内显示终端缓冲区，缓冲区的行数应按区域高度设置
+    pub fn buffer(&mut self, buffer: &Buffer, font: &dyn FontFace) {
+        crate::draw_buffer(buffer, self.rect.x, self.rect.y, font);
+    }
+}
+
+/// 屏幕布局：持有显示屏和命名区域，记录帧缓冲中每页改变过的列
+pub struct Layout {
+    display: Display,
+    regions: BTreeMap<String, Entry>,
+    dirty: [Option<Range<usize>>; PAGE],
+}
+
+impl Layout {
+    /// 接管显示屏，帧缓冲从空白开始；第一次 `flush` 发送整个屏幕，清掉屏上原有的内容
+    pub fn new(mut display: Display) -> Self {
+        display.newframe();
+        Layout {
+            display,
+            regions: BTreeMap::new(),
+            dirty: std::array::from_fn(|_| Some(0..WIDTH)),
+        }
+    }
+
+    /// 添加区域，同名区域会被替换，超出屏幕的部分被截掉
+    pub fn region(mut self, name: &str, rect: Rect) -> Self {
+        self.define(name, rect);
+        self
+    }
+
+    /// 运行时添加或修改区域，不影响已有的占用
+    pub fn define(&mut self, name: &str, rect: Rect) {
+        let x = rect.x.min(WIDTH as u8);
+        let y = rect.y.min(HEIGHT as u8);
+        let rect = Rect::new(
+            x,
+            y,
+            rect.w.min(WIDTH as u8 - x),
+            rect.h.min(HEIGHT as u8 - y),
+        );
+        self.regions
+            .entry(name.to_string())
+            .and_modify(|e| e.rect = rect)
+            .or_insert(Entry {
+                rect,
+                claimed: false,
+            });
+    }
+
+    pub fn remove(&mut self, name: &str) {
+        self.regions.remove(name);
+    }
+
+    /// 区域在屏幕上的位置
+    pub fn bounds(&self, name: &str) -> Option<Rect> {
+        self.regions.get(name).map(|e| e.rect)
+    }
+
+    pub fn names(&self) -> impl Iterator<Item = &str> {
+        self.regions.keys().map(String::as_str)
+    }
+
+    /// 在区域内绘制，区域以外的像素保持不变
+    pub fn draw<R>(
+        &mut self,
+        name: &str,
+        f: impl FnOnce(&mut Canvas) -> R,
+    ) -> Result<R, RegionError> {
+        let rect = self
+            .bounds(name)
+            .ok_or_else(|| RegionError::Unknown(name.to_string()))?;
+        Ok(self.draw_in(rect, f))
+    }
+
+    /// 在任意矩形内绘制，用于不属于任何区域的内容
+    pub fn draw_in<R>(&mut self, rect: Rect, f: impl FnOnce(&mut Canvas) -> R) -> R {
+        let saved = crate::frame();
+        let mut canvas = Canvas {
+            rect,
+            fonts: self.display.fonts(),
+        };
+        let result = f(&mut canvas);
+
+        // 把区域以外的部分恢复，同时记下改变过的列
+        let mut frame = crate::frame();
+        let xs = rect.x as usize..rect.x as usize + rect.w as usize;
+        let ys = rect.y as usize..rect.y as usize + rect.h as usize;
+        for (page, (row, old)) in frame.iter_mut().zip(saved.iter()).enumerate() {
+            let mask = (0..8)
+                .filter(|bit| ys.contains(&(page * 8 + bit)))
+                .fold(0u8, |m, bit| m | (1 << bit));
+            let mut changed: Option<Range<usize>> = None;
+            for (x, (byte, old)) in row.iter_mut().zip(old).enumerate() {
+                let m = if xs.contains(&x) { mask } else { 0 };
+                *byte = (*byte & m) | (*old & !m);
+                if *byte != *old {
+                    changed = Some(changed.map_or(x..x + 1, |c| c.start..x + 1));
+                }
+            }
+            if let Some(c) = changed {
+                self.mark(page, c);
+            }
+        }
+        crate::load_frame(&frame);
+        result
+    }
+
+    fn mark(&mut self, page: usize, columns: Range<usize>) {
+        let dirty = &mut self.dirty[page];
+        *dirty = Some(match dirty.take() {
+            Some(d) => d.start.min(columns.start)..d.end.max(columns.end),
+            None => columns,
+        });
+    }
+
+    /// 清空整个屏幕
+    pub fn clear(&mut self) {
+        self.draw_in(Rect::screen(), |c| c.clear());
+    }
+
+    /// 是否有未发送的改变
+    pub fn is_dirty(&self) -> bool {
+        self.dirty.iter().any(Option::is_some)
+    }
+
+    /// 只把改变过的列发送到屏幕
+    pub fn flush(&mut self) {
+        for page in 0..PAGE {
+            if let Some(columns) = self.dirty[page].take() {
+                self.display.showframe_span(page, columns);
+            }
+        }
+    }
+
+    /// 发送整个帧缓冲，例如屏幕重新初始化之后
+    pub fn flush_all(&mut self) {
+        self.dirty = Default::default();
+        self.display.showframe();
+    }
+
+    pub fn display(&self) -> &Display {
+        &self.display
+    }
+
+    pub fn display_mut(&mut self) -> &mut Display {
+        &mut self.display
+    }
+
+    /// 放到互斥锁中，多个组件各自占用区域
+    pub fn share(self) -> SharedLayout {
+        SharedLayout(Arc::new(Mutex::new(self)))
+    }
+}
+
+/// 可以在线程之间共享的布局
+#[derive(Clone)]
+pub struct SharedLayout(Arc<Mutex<Layout>>);
+
+impl SharedLayout {
+    /// 占用区域，同一时间只有一个组件能占用；返回的句柄被丢弃时释放
+    pub fn claim(&self, name: &str) -> Result<Region, RegionError> {
+        let mut layout = self.lock();
+        let entry = layout
+            .regions
+            .get_mut(name)
+            .ok_or_else(|| RegionError::Unknown(name.to_string()))?;
+        if entry.claimed {
+            return Err(RegionError::Claimed(name.to_string()));
+        }
+        entry.claimed = true;
+        Ok(Region {
+            layout: self.clone(),
+            name: name.to_string(),
+        })
+    }
+
+    /// 直接访问布局，例如调整区域或刷新屏幕
+    pub fn lock(&self) -> MutexGuard<'_, Layout> {
+        // 绘制中的组件崩溃不影响其它组件继续使用屏幕
+        self.0.lock().unwrap_or_else(|e| e.into_inner())
+    }
+}
+
+/// 被占用的区域
+pub struct Region {
+    layout: SharedLayout,
+    name: String,
+}
+
+impl Region {
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    pub fn bounds(&self) -> Option<Rect> {
+        self.layout.lock().bounds(&self.name)
+    }
+
+    /// 在区域内绘制，区域被删除时返回错误
+    pub fn draw<R>(&self, f: impl FnOnce(&mut Canvas) -> R) -> Result<R, RegionError> {
+        self.layout.lock().draw(&self.name, f)
+    }
+
+    /// 把屏幕上改变过的部分发送出去，包括其它区域未刷新的改变
+    pub fn flush(&self) {
+        self.layout.lock().flush();
+    }
+}
+
+impl Drop for Region {
+    fn drop(&mut self) {
+        if let Some(entry) = self.layout.lock().regions.get_mut(&self.name) {
+            entry.claimed = false;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::frame_lock;
+
+    fn layout() -> Layout {
+        let mut layout = Layout::new(Display::simulated()).region("box", Rect::new(10, 12, 20, 8));
+        layout.flush();
+        layout
+    }
+
+    #[test]
+    fn new_layout_sends_the_whole_screen() {
+        let _lock = frame_lock();
+        let mut layout = Layout::new(Display::simulated());
+        assert!(layout.is_dirty());
+        assert!(layout.dirty.iter().all(|d| *d == Some(0..WIDTH)));
+        layout.flush();
+        assert!(!layout.is_dirty());
+    }
+
+    #[test]
+    fn drawing_is_clipped_to_the_region() {
+        let _lock = frame_lock();
+        let mut layout = layout();
+        crate::load_frame(&[[0xff; WIDTH]; PAGE]);
+        layout
+            .draw("box", |c| c.fill_rect(-100, -100, 1000, 1000, false))
+            .unwrap();
+        let frame = Image::from_frame();
+        for y in 0..HEIGHT {
+            for x in 0..WIDTH {
+                let inside = (10..30).contains(&x) && (12..20).contains(&y);
+                assert_eq!(frame.pixel(x, y), !inside, "({}, {})", x, y);
+            }
+        }
+        assert_eq!(
+            layout.draw("nope", |_| ()),
+            Err(RegionError::Unknown("nope".to_string()))
+        );
+    }
+
+    #[test]
+    fn dirty_columns_per_page() {
+        let _lock = frame_lock();
+        let mut layout = layout();
+        layout.draw("box", |c| c.pixel(0, 0, true)).unwrap();
+        layout.draw("box", |c| c.pixel(15, 2, true)).unwrap();
+        // 没有改变像素的绘制不记录
+        layout.draw("box", |c| c.pixel(19, 7, false)).unwrap();
+        // 区域以外的像素被恢复，也不记录
+        layout.draw("box", |c| c.pixel(-5, 0, true)).unwrap();
+        let mut expected: [Option<Range<usize>>; PAGE] = Default::default();
+        expected[1] = Some(10..26);
+        assert_eq!(layout.dirty, expected);
+
+        layout.draw("box", |c| c.line(0, 7, 19, 7, true)).unwrap();
+        expected[1] = Some(10..26);
+        expected[2] = Some(10..30);
+        assert_eq!(layout.dirty, expected);
+        layout.flush();
+        assert!(!layout.is_dirty());
+    }
+
+    #[test]
+    fn claims_are_exclusive() {
+        let _lock = frame_lock();
+        let shared = layout().share();
+        let region = shared.claim("box").unwrap();
+        assert_eq!(
+            shared.claim("box").err(),
+            Some(RegionError::Claimed("box".to_string()))
+        );
+        drop(region);
+        assert!(shared.claim("box").is_ok());
+    }
+}
@@ -0,0 +1,186 @@
+use super::{glyph_pixel, measure_text};
+use crate::font::FontFace;
+use crate::{HEIGHT, WIDTH, print_string, setpixel};
+
+/// 文字方向，只影响本次绘制，与整屏旋转无关
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum TextDirection {
+    /// 正常的横排
+    #[default]
+    Horizontal,
+    /// 顺时针旋转90度，从上往下读
+    Rotate90,
+    /// 逆时针旋转90度，从下往上读
+    Rotate270,
+    /// 竖排：字符不旋转，逐个向下排列，适合中文；`'\n'` 换到右边一列
+    Stacked,
+}
+
+fn plot(x: i32, y: i32, on: bool) {
+    if x >= 0 && y >= 0 && (x as usize) < WIDTH && (y as usize) < HEIGHT {
+        setpixel(x as u8, y as u8, on);
+    }
+}
+
+/// 按方向绘制单行文字，`(x, y)` 为文字外框的左上角，返回外框尺寸 `(宽, 高)`。
+/// 字体不支持的字符会被跳过
+pub fn draw_text_directed(
+    x: u8,
+    y: u8,
+    font: &dyn FontFace,
+    text: &str,
+    dir: TextDirection,
+) -> (u16, u16) {
+    let h = font.get_font_height() as i32;
+    match dir {
+        TextDirection::Horizontal => {
+            let _ = print_string(x, y, font, text);
+            measure_text(font, text)
+        }
+        TextDirection::Rotate90 | TextDirection::Rotate270 => {
+            // 先排版得到每个字符的位置和总长度
+            let mut pen = 0i32;
+            let mut prev = None;
+            let mut placed = Vec::new();
+            for ch in text.chars() {
+                let Some(m) = font.get_metrics(ch) else {
+                    continue;
+                };
+                if let Some(p) = prev {
+                    pen += font.get_kerning(p, ch) as i32;
+                }
+                prev = Some(ch);
+                placed.push((ch, pen, m));
+                pen += m.advance as i32;
+            }
+            let len = pen;
+
+            for (ch, pen, m) in placed {
+                let glyph = font.get_glyph(ch);
+                for gx in 0..m.advance as i32 {
+                    let col = gx - m.bearing as i32;
+                    for gy in 0..h {
+                        let on = glyph_pixel(
+                            glyph.as_ref(),
+                            m.left as usize,
+                            m.width as usize,
+                            col,
+                            gy as usize,
+                        );
+                        let along = pen + gx;
+                        let (sx, sy) = if dir == TextDirection::Rotate90 {
+                            (h - 1 - gy, along)
+                        } else {
+                            (gy, len - 1 - along)
+                        };
+                        plot(x as i32 + sx, y as i32 + sy, on);
+                    }
+                }
+            }
+            (h as u16, len.max(0) as u16)
+        }
+        TextDirection::Stacked => {
+            let mut column_x = x as i32;
+            let mut max_w = 0;
+            let mut max_h = 0;
+            for line in text.split('\n') {
+                // 每列的宽度取最宽的字符，字符在列内居中
+                let col_w = line
+                    .chars()
+                    .filter_map(|c| font.get_metrics(c))
+                    .map(|m| m.advance as i32)
+                    .max()
+                    .unwrap_or(0);
+                let mut row_y = y as i32;
+                for ch in line.chars() {
+                    let Some(m) = font.get_metrics(ch) else {
+                        continue;
+                    };
+                    let glyph = font.get_glyph(ch);
+                    let offset = (col_w - m.advance as i32) / 2;
+                    for gx in 0..m.advance as i32 {
+                        let col = gx - m.bearing as i32;
+                        for gy in 0..h {
+                            let on = glyph_pixel(
+                                glyph.as_ref(),
+                                m.left as usize,
+                                m.width as usize,
+                                col,
+                                gy as usize,
+                            );
+                            plot(column_x + offset + gx, row_y + gy, on);
+                        }
+                    }
+                    row_y += h;
+                }
+                max_h = max_h.max(row_y - y as i32);
+                column_x += col_w;
+                max_w = column_x - x as i32;
+            }
+            (max_w.max(0) as u16, max_h.max(0) as u16)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::font::{FONT8X8, Font};
+    use crate::image::Image;
+    use crate::{frame_lock, newframe};
+
+    // 只点亮字符格左上角一个像素的测试字体
+    const DOT: Font = FONT8X8.with_glyphs(6, 8, &['中'], &[1, 0, 0, 0, 0, 0]);
+
+    fn lit() -> Vec<(usize, usize)> {
+        let frame = Image::from_frame();
+        (0..HEIGHT)
+            .flat_map(|y| (0..WIDTH).map(move |x| (x, y)))
+            .filter(|&(x, y)| frame.pixel(x, y))
+            .collect()
+    }
+
+    #[test]
+    fn rotated_text() {
+        let _lock = frame_lock();
+        newframe();
+        let size = draw_text_directed(10, 20, &DOT, "中中", TextDirection::Rotate90);
+        assert_eq!(size, (8, 12));
+        // 字符顶部朝右，从上往下排
+        assert_eq!(lit(), [(17, 20), (17, 26)]);
+
+        newframe();
+        let size = draw_text_directed(10, 20, &DOT, "中中", TextDirection::Rotate270);
+        assert_eq!(size, (8, 12));
+        // 字符顶部朝左，从下往上排
+        assert_eq!(lit(), [(10, 25), (10, 31)]);
+    }
+
+    #[test]
+    fn stacked_text() {
+        let _lock = frame_lock();
+        newframe();
+        let size = draw_text_directed(10, 20, &DOT, "中\n中中", TextDirection::Stacked);
+        assert_eq!(size, (12, 16));
+        assert_eq!(lit(), [(10, 20), (16, 20), (16, 28)]);
+    }
+
+    #[test]
+    fn clipped_at_bottom_and_right() {
+        let _lock = frame_lock();
+        newframe();
+        let size = draw_text_directed(0, 60, &DOT, "中中", TextDirection::Rotate90);
+        assert_eq!(size, (8, 12));
+        assert_eq!(lit(), [(7, 60)]);
+
+        newframe();
+        let size = draw_text_directed(124, 0, &DOT, "中\n中", TextDirection::Stacked);
+        assert_eq!(size, (12, 8));
+        assert_eq!(lit(), [(124, 0)]);
+
+        newframe();
+        let size = draw_text_directed(124, 0, &DOT, "中", TextDirection::Rotate270);
+        assert_eq!(size, (8, 6));
+        assert_eq!(lit(), [(124, 5)]);
+    }
+}
@@ -0,0 +1,393 @@
+use super::{TextStyle, styled_width};
+use crate::font::{FontFace, FontRegistry, Icon, MissingGlyphs, SharedFont};
+use crate::print_string_styled;
+use std::fmt;
+
+/// 标记文本解析或绘制时的错误
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum MarkupError {
+    /// 无法识别的标签，例如 `[foo]`
+    UnknownTag(String),
+    /// 没有这个名称的图标
+    UnknownIcon(String),
+    /// 字体表中没有这个字体
+    UnknownFont(String),
+    /// 结束标签与开始标签不匹配
+    Unbalanced(String),
+    /// 字体不支持的字符
+    Missing(MissingGlyphs),
+}
+
+impl fmt::Display for MarkupError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            MarkupError::UnknownTag(t) => write!(f, "unknown tag [{}]", t),
+            MarkupError::UnknownIcon(i) => write!(f, "unknown icon {}", i),
+            MarkupError::UnknownFont(n) => write!(f, "unknown font {}", n),
+            MarkupError::Unbalanced(t) => write!(f, "unbalanced tag [{}]", t),
+            MarkupError::Missing(m) => m.fmt(f),
+        }
+    }
+}
+
+impl std::error::Error for MarkupError {}
+
+// 字体切换方式
+#[derive(Clone, Debug, PartialEq, Eq)]
+enum FontRef {
+    Named(String),
+    Height(u8),
+}
+
+/// 一段样式相同的文字
+#[derive(Clone, Debug, PartialEq, Eq)]
+struct Span {
+    text: String,
+    style: TextStyle,
+    font: Option<FontRef>,
+}
+
+// 可以嵌套的标签
+#[derive(Clone, Debug, PartialEq, Eq)]
+enum Tag {
+    Bold,
+    Stars, // ** 粗体简写
+    Italic,
+    Underline,
+    Strike,
+    Invert,
+    Font(FontRef),
+}
+
+impl Tag {
+    fn name(&self) -> &'static str {
+        match self {
+            Tag::Bold => "b",
+            Tag::Stars => "**",
+            Tag::Italic => "i",
+            Tag::Underline => "u",
+            Tag::Strike => "s",
+            Tag::Invert => "inv",
+            Tag::Font(FontRef::Named(_)) => "font",
+            Tag::Font(FontRef::Height(_)) => "size",
+        }
+    }
+}
+
+/// 解析后的标记文本，可以在一行内混排字体、样式和图标。
+///
+/// 语法：
+/// - `**粗体**`，或 `[b]..[/b]`
+/// - `[i]斜体[/i]`、`[u]下划线[/u]`、`[s]删除线[/s]`、`[inv]反色[/inv]`
+/// - `[font:16x16]..[/font]` 按名称切换字体，`[size:24]..[/size]` 按高度切换
+/// - `[icon:battery-full]` 插入图标
+/// - `\` 转义下一个字符，`\n` 换行
+///
+/// 同一行中高度不同的字体按底部对齐。
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct RichText {
+    spans: Vec<Span>,
+}
+
+impl RichText {
+    pub fn parse(markup: &str) -> Result<RichText, MarkupError> {
+        let mut spans: Vec<Span> = Vec::new();
+        let mut stack: Vec<Tag> = Vec::new();
+        let mut text = String::new();
+
+        // 按当前标签栈计算样式和字体
+        let current = |stack: &[Tag]| {
+            let mut style = TextStyle::PLAIN;
+            let mut font = None;
+            for tag in stack {
+                match tag {
+                    Tag::Bold | Tag::Stars => style.bold = true,
+                    Tag::Italic => style.italic = true,
+                    Tag::Underline => style.underline = true,
+                    Tag::Strike => style.strikethrough = true,
+                    Tag::Invert => style.invert = true,
+                    Tag::Font(f) => font = Some(f.clone()),
+                }
+            }
+            (style, font)
+        };
+        let flush = |text: &mut String, spans: &mut Vec<Span>, stack: &[Tag]| {
+            if !text.is_empty() {
+                let (style, font) = current(stack);
+                spans.push(Span {
+                    text: std::mem::take(text),
+                    style,
+                    font,
+                });
+            }
+        };
+
+        let mut chars = markup.chars().peekable();
+        while let Some(ch) = chars.next() {
+            match ch {
+                '\\' => match chars.next() {
+                    Some('n') => text.push('\n'),
+                    Some(c) => text.push(c),
+                    None => text.push('\\'),
+                },
+                '*' if chars.peek() == Some(&'*') => {
+                    chars.next();
+                    flush(&mut text, &mut spans, &stack);
+                    // 已经打开时是结束标记，中间的标签必须先结束
+                    if !stack.contains(&Tag::Stars) {
+                        stack.push(Tag::Stars);
+                    } else if stack.pop() != Some(Tag::Stars) {
+                        return Err(MarkupError::Unbalanced("**".to_string()));
+                    }
+                }
+                '[' => {
+                    let mut tag = String::new();
+                    for c in chars.by_ref() {
+                        if c == ']' {
+                            break;
+                        }
+                        tag.push(c);
+                    }
+                    flush(&mut text, &mut spans, &stack);
+                    if let Some(name) = tag.strip_prefix('/') {
+                        match stack.pop() {
+                            Some(open) if open != Tag::Stars && open.name() == name => {}
+                            _ => return Err(MarkupError::Unbalanced(tag)),
+                        }
+                        continue;
+                    }
+                    let (key, value) = tag.split_once(':').unwrap_or((tag.as_str(), ""));
+                    let open = match key {
+                        "b" => Tag::Bold,
+                        "i" => Tag::Italic,
+                        "u" => Tag::Underline,
+                        "s" => Tag::Strike,
+                        "inv" => Tag::Invert,
+                        "font" => Tag::Font(FontRef::Named(value.to_string())),
+                        "size" => match value.parse() {
+                            Ok(h) => Tag::Font(FontRef::Height(h)),
+                            Err(_) => return Err(MarkupError::UnknownTag(tag)),
+                        },
+                        "icon" => {
+                            let icon = Icon::from_name(value)
+                                .ok_or_else(|| MarkupError::UnknownIcon(value.to_string()))?;
+                            text.push(icon.char());
+                            continue;
+                        }
+                        _ => return Err(MarkupError::UnknownTag(tag)),
+                    };
+                    stack.push(open);
+                }
+                c => text.push(c),
+            }
+        }
+        flush(&mut text, &mut spans, &stack);
+        if let Some(open) = stack.pop() {
+            return Err(MarkupError::Unbalanced(open.name().to_string()));
+        }
+        Ok(RichText { spans })
+    }
+
+    /// 去掉标记后的纯文字
+    pub fn plain_text(&self) -> String {
+        self.spans.iter().map(|s| s.text.as_str()).collect()
+    }
+
+    // 按 '\n' 分行，每行是若干 (文字, 样式, 字体)
+    fn lines<'a>(
+        &'a self,
+        fonts: &FontRegistry,
+        default: &'a dyn FontFace,
+    ) -> Result<Vec<Vec<Part<'a>>>, MarkupError> {
+        let mut lines = vec![Vec::new()];
+        for span in &self.spans {
+            let font = match &span.font {
+                None => Resolved::Default(default),
+                Some(FontRef::Named(name)) => Resolved::Shared(
+                    fonts
+                        .get(name)
+                        .ok_or_else(|| MarkupError::UnknownFont(name.clone()))?,
+                ),
+                Some(FontRef::Height(h)) => Resolved::Shared(
+                    fonts
+                        .by_height(*h)
+                        .ok_or_else(|| MarkupError::UnknownFont(h.to_string()))?,
+                ),
+            };
+            for (i, part) in span.text.split('\n').enumerate() {
+                if i > 0 {
+                    lines.push(Vec::new());
+                }
+                if !part.is_empty() {
+                    lines
+                        .last_mut()
+                        .unwrap()
+                        .push((part, span.style, font.clone()));
+                }
+            }
+        }
+        Ok(lines)
+    }
+
+    /// 绘制后的尺寸 `(宽, 高)`
+    pub fn measure(
+        &self,
+        fonts: &FontRegistry,
+        default: &dyn FontFace,
+    ) -> Result<(u16, u16), MarkupError> {
+        let mut w = 0;
+        let mut h = 0;
+        for line in self.lines(fonts, default)? {
+            w = w.max(
+                line.iter()
+                    .map(|(t, s, f)| styled_width(f.get(), t, *s))
+                    .sum(),
+            );
+            h += line_height(&line, default);
+        }
+        Ok((w, h))
+    }
+
+    /// 从 `(x, y)` 开始绘制，返回绘制的尺寸
+    pub fn draw(
+        &self,
+        x: u8,
+        y: u8,
+        fonts: &FontRegistry,
+        default: &dyn FontFace,
+    ) -> Result<(u16, u16), MarkupError> {
+        let mut missing = Vec::new();
+        let mut w = 0;
+        let mut row = y as u16;
+        for line in self.lines(fonts, default)? {
+            let line_h = line_height(&line, default);
+            let mut column = x as u16;
+            for (text, style, font) in &line {
+                let font = font.get();
+                // 底部对齐
+                let top = row + line_h - font.get_font_height() as u16;
+                if column <= u8::MAX as u16
+                    && top <= u8::MAX as u16
+                    && let Err(MissingGlyphs(m)) =
+                        print_string_styled(column as u8, top as u8, font, text, *style)
+                {
+                    missing.extend(m);
+                }
+                column += styled_width(font, text, *style);
+            }
+            w = w.max(column - x as u16);
+            row += line_h;
+        }
+        if missing.is_empty() {
+            Ok((w, row - y as u16))
+        } else {
+            Err(MarkupError::Missing(MissingGlyphs(missing)))
+        }
+    }
+}
+
+// 解析后的字体，默认字体借用，字体表中的字体共享
+#[derive(Clone)]
+enum Resolved<'a> {
+    Default(&'a dyn FontFace),
+    Shared(SharedFont),
+}
+
+// 一行中的一段：文字、样式、字体
+type Part<'a> = (&'a str, TextStyle, Resolved<'a>);
+
+impl Resolved<'_> {
+    fn get(&self) -> &dyn FontFace {
+        match self {
+            Resolved::Default(f) => *f,
+            Resolved::Shared(f) => f.as_ref(),
+        }
+    }
+}
+
+fn line_height(line: &[Part], default: &dyn FontFace) -> u16 {
+    line.iter()
+        .map(|(_, _, f)| f.get().get_font_height())
+        .max()
+        .unwrap_or(default.get_font_height()) as u16
+}
+
+/// 解析并绘制标记文本，见 `RichText`
+pub fn draw_markup(
+    x: u8,
+    y: u8,
+    fonts: &FontRegistry,
+    default: &dyn FontFace,
+    markup: &str,
+) -> Result<(u16, u16), MarkupError> {
+    RichText::parse(markup)?.draw(x, y, fonts, default)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::font::{FONT8X8, FONT16X16_PROP, FONT24X24};
+    use crate::{frame_lock, newframe};
+
+    #[test]
+    fn status_line_example_draws() {
+        let _lock = frame_lock();
+        newframe();
+        let fonts = FontRegistry::with_builtin();
+        let font = fonts.get("8x8").unwrap();
+        let markup = "CPU **87%** [icon:temp] [size:16]62°C[/size]";
+        assert!(draw_markup(0, 0, &fonts, font.as_ref(), markup).is_ok());
+        // 没有图标的字体仍然报告缺字
+        assert_eq!(
+            draw_markup(0, 0, &fonts, &FONT8X8, "[icon:temp]"),
+            Err(MarkupError::Missing(MissingGlyphs(vec![
+                Icon::Thermometer.char()
+            ])))
+        );
+    }
+
+    #[test]
+    fn degree_sign_in_builtin_fonts() {
+        for font in [&FONT8X8 as &dyn FontFace, &FONT16X16_PROP, &FONT24X24] {
+            let metrics = font.get_metrics('°').unwrap();
+            assert!(metrics.advance > 0 && metrics.advance <= font.get_char_width('M').unwrap());
+            assert!(font.get_glyph('°').is_some());
+        }
+    }
+
+    fn styles(markup: &str) -> Result<Vec<(String, TextStyle)>, MarkupError> {
+        let text = RichText::parse(markup)?;
+        Ok(text.spans.into_iter().map(|s| (s.text, s.style)).collect())
+    }
+
+    #[test]
+    fn stars_nest_with_tags() {
+        let bold = TextStyle::bold();
+        let both = TextStyle {
+            italic: true,
+            ..bold
+        };
+        assert_eq!(
+            styles("**a[i]b[/i]c**d").unwrap(),
+            [
+                ("a".to_string(), bold),
+                ("b".to_string(), both),
+                ("c".to_string(), bold),
+                ("d".to_string(), TextStyle::PLAIN),
+            ]
+        );
+        assert_eq!(styles("[i]**b**[/i]").unwrap(), [("b".to_string(), both)]);
+    }
+
+    #[test]
+    fn mismatched_closers() {
+        let unbalanced = |t: &str| Err(MarkupError::Unbalanced(t.to_string()));
+        // ** 不能结束在其后打开的标签之前
+        assert_eq!(styles("**a[i]b**[/i]"), unbalanced("**"));
+        // [/b] 不能结束 **，[b] 也不能由 ** 结束
+        assert_eq!(styles("**a[/b]"), unbalanced("/b"));
+        assert_eq!(styles("[b]a**b**[/b]").map(|s| s.len()), Ok(2));
+        assert_eq!(styles("**a"), unbalanced("**"));
+        assert_eq!(styles("[i]a[/u]"), unbalanced("/u"));
+    }
+}
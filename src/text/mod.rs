@@ -0,0 +1,254 @@
+use crate::font::{FontFace, Glyph};
+use crate::print_string;
+
+mod direction;
+mod markup;
+mod style;
+mod wrap;
+pub use direction::{TextDirection, draw_text_directed};
+pub use markup::{MarkupError, RichText, draw_markup};
+pub use style::TextStyle;
+pub use wrap::{TextBox, TextLayout, wrap_text};
+
+/// 屏幕上的矩形区域
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct Rect {
+    pub x: u8,
+    pub y: u8,
+    pub w: u8,
+    pub h: u8,
+}
+
+impl Rect {
+    pub const fn new(x: u8, y: u8, w: u8, h: u8) -> Self {
+        Rect { x, y, w, h }
+    }
+
+    /// 整个屏幕
+    pub const fn screen() -> Self {
+        Rect::new(0, 0, crate::WIDTH as u8, crate::HEIGHT as u8)
+    }
+}
+
+/// 水平对齐方式
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum HAlign {
+    #[default]
+    Left,
+    Center,
+    Right,
+}
+
+/// 垂直对齐方式
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum VAlign {
+    #[default]
+    Top,
+    Middle,
+    Bottom,
+}
+
+impl std::str::FromStr for HAlign {
+    type Err = String;
+
+    /// 解析 `left`、`center` 或 `right`
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "left" => Ok(HAlign::Left),
+            "center" => Ok(HAlign::Center),
+            "right" => Ok(HAlign::Right),
+            _ => Err(format!("bad align: {}", s)),
+        }
+    }
+}
+
+impl std::str::FromStr for VAlign {
+    type Err = String;
+
+    /// 解析 `top`、`middle` 或 `bottom`
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "top" => Ok(VAlign::Top),
+            "middle" => Ok(VAlign::Middle),
+            "bottom" => Ok(VAlign::Bottom),
+            _ => Err(format!("bad valign: {}", s)),
+        }
+    }
+}
+
+// 字符在 (col, row) 处是否点亮，col 从有效列起算（即光标处为0）
+pub(crate) fn glyph_pixel(
+    glyph: Option<&Glyph>,
+    left: usize,
+    width: usize,
+    col: i32,
+    row: usize,
+) -> bool {
+    col >= 0 && (col as usize) < width && glyph.is_some_and(|g| g.pixel(left + col as usize, row))
+}
+
+/// 单行文字的宽度，按字符前进宽度和字距累加，最后一个字符只计算笔画部分。
+/// 字体不支持的字符不占宽度
+pub fn measure_line(font: &dyn FontFace, line: &str) -> u16 {
+    let mut pen: i32 = 0;
+    let mut extent: i32 = 0;
+    let mut prev = None;
+    for ch in line.chars() {
+        let Some(m) = font.get_metrics(ch) else {
+            continue;
+        };
+        if let Some(p) = prev {
+            pen += font.get_kerning(p, ch) as i32;
+        }
+        prev = Some(ch);
+        let ink = if m.width > 0 {
+            m.bearing as i32 + m.width as i32
+        } else {
+            m.advance as i32
+        };
+        extent = extent.max(pen + ink);
+        pen += m.advance as i32;
+    }
+    extent.max(0) as u16
+}
+
+// 按样式绘制时的前进宽度，加粗每个字符多占一列
+pub(crate) fn styled_width(font: &dyn FontFace, text: &str, style: TextStyle) -> u16 {
+    let bold = if style.bold {
+        text.chars()
+            .filter(|&c| font.get_metrics(c).is_some())
+            .count() as u16
+    } else {
+        0
+    };
+    let mut w = 0;
+    let mut prev = None;
+    for ch in text.chars() {
+        let Some(m) = font.get_metrics(ch) else {
+            continue;
+        };
+        if let Some(p) = prev {
+            w += font.get_kerning(p, ch) as i32;
+        }
+        prev = Some(ch);
+        w += m.advance as i32;
+    }
+    w.max(0) as u16 + bold
+}
+
+/// 文字渲染后的尺寸 `(宽, 高)`，按 `'\n'` 分行，宽度取最长的一行
+pub fn measure_text(font: &dyn FontFace, text: &str) -> (u16, u16) {
+    let mut w = 0;
+    let mut lines = 0;
+    for line in text.split('\n') {
+        w = w.max(measure_line(font, line));
+        lines += 1;
+    }
+    (w, lines * font.get_font_height() as u16)
+}
+
+impl HAlign {
+    /// 宽度为 `len` 的内容在 `rect` 中的起始横坐标，放不下时贴齐左边
+    pub fn place(self, rect: Rect, len: u16) -> u8 {
+        let free = (rect.w as u16).saturating_sub(len) as u8;
+        rect.x.saturating_add(match self {
+            HAlign::Left => 0,
+            HAlign::Center => free / 2,
+            HAlign::Right => free,
+        })
+    }
+}
+
+impl VAlign {
+    /// 高度为 `len` 的内容在 `rect` 中的起始纵坐标，放不下时贴齐顶部
+    pub fn place(self, rect: Rect, len: u16) -> u8 {
+        let free = (rect.h as u16).saturating_sub(len) as u8;
+        rect.y.saturating_add(match self {
+            VAlign::Top => 0,
+            VAlign::Middle => free / 2,
+            VAlign::Bottom => free,
+        })
+    }
+}
+
+/// 在矩形区域内按对齐方式绘制文字，多行文字每行单独水平对齐
+pub fn draw_text_aligned(rect: Rect, font: &dyn FontFace, text: &str, h: HAlign, v: VAlign) {
+    let (_, text_h) = measure_text(font, text);
+    let line_h = font.get_font_height();
+    let mut y = v.place(rect, text_h);
+    for line in text.split('\n') {
+        let x = h.place(rect, measure_line(font, line));
+        let _ = print_string(x, y, font, line);
+        y = y.saturating_add(line_h);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::font::{FONT8X8, Font, GlyphMetrics, KernPair};
+
+    // 两个比例字符：α 笔画 3 列、前进 4；β 左侧留 1 列、笔画 4 列、前进 6；αβ 之间收紧 2 列
+    const PROP: Font = FONT8X8
+        .with_glyphs(6, 8, &['α', 'β'], &[0; 12])
+        .with_glyph_metrics(&[
+            GlyphMetrics {
+                left: 0,
+                width: 3,
+                bearing: 0,
+                advance: 4,
+            },
+            GlyphMetrics {
+                left: 1,
+                width: 4,
+                bearing: 1,
+                advance: 6,
+            },
+        ])
+        .with_kerning(&[KernPair {
+            left: 'α',
+            right: 'β',
+            adjust: -2,
+        }]);
+
+    #[test]
+    fn proportional_widths_with_kerning() {
+        // α 前进 4，字距 -2，β 的笔画从 2 + 1 到 2 + 1 + 4
+        assert_eq!(measure_line(&PROP, "αβ"), 7);
+        assert_eq!(measure_line(&PROP, "βα"), 9);
+        // 不支持的字符不占宽度，也不打断字距
+        assert_eq!(measure_line(&PROP, "α中β"), 7);
+        assert_eq!(measure_line(&FONT8X8, "abc"), 18);
+    }
+
+    #[test]
+    fn multi_line_height() {
+        assert_eq!(measure_text(&PROP, "αβ\nβα\nα"), (9, 24));
+        assert_eq!(measure_text(&FONT8X8, ""), (0, 8));
+        assert_eq!(measure_text(&FONT8X8, "ab\n"), (12, 16));
+    }
+
+    #[test]
+    fn horizontal_placement() {
+        let rect = Rect::new(10, 0, 20, 8);
+        assert_eq!(HAlign::Left.place(rect, 7), 10);
+        assert_eq!(HAlign::Center.place(rect, 7), 16);
+        assert_eq!(HAlign::Right.place(rect, 7), 23);
+        // 比矩形宽时贴齐左边
+        for align in [HAlign::Left, HAlign::Center, HAlign::Right] {
+            assert_eq!(align.place(rect, 300), 10);
+        }
+    }
+
+    #[test]
+    fn vertical_placement() {
+        let rect = Rect::new(0, 5, 0, 30);
+        assert_eq!(VAlign::Top.place(rect, 24), 5);
+        assert_eq!(VAlign::Middle.place(rect, 24), 8);
+        assert_eq!(VAlign::Bottom.place(rect, 24), 11);
+        // 比矩形高时贴齐顶部
+        for align in [VAlign::Top, VAlign::Middle, VAlign::Bottom] {
+            assert_eq!(align.place(rect, 40), 5);
+        }
+    }
+}
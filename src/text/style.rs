@@ -0,0 +1,68 @@
+/// 文字样式，全部由现有点阵合成，不需要额外的字体
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct TextStyle {
+    /// 加粗：点阵向右错开一列再叠加一次，字符宽度加1
+    pub bold: bool,
+    /// 下划线，位于字体最底下一行
+    pub underline: bool,
+    /// 删除线，位于字体高度的一半
+    pub strikethrough: bool,
+    /// 斜体：按行向右错切，越靠上偏移越大
+    pub italic: bool,
+    /// 反色：白底黑字高亮
+    pub invert: bool,
+}
+
+impl TextStyle {
+    pub const PLAIN: TextStyle = TextStyle {
+        bold: false,
+        underline: false,
+        strikethrough: false,
+        italic: false,
+        invert: false,
+    };
+
+    pub const fn bold() -> Self {
+        TextStyle {
+            bold: true,
+            ..TextStyle::PLAIN
+        }
+    }
+
+    pub const fn underline() -> Self {
+        TextStyle {
+            underline: true,
+            ..TextStyle::PLAIN
+        }
+    }
+
+    pub const fn strikethrough() -> Self {
+        TextStyle {
+            strikethrough: true,
+            ..TextStyle::PLAIN
+        }
+    }
+
+    pub const fn italic() -> Self {
+        TextStyle {
+            italic: true,
+            ..TextStyle::PLAIN
+        }
+    }
+
+    pub const fn inverted() -> Self {
+        TextStyle {
+            invert: true,
+            ..TextStyle::PLAIN
+        }
+    }
+
+    pub fn is_plain(&self) -> bool {
+        *self == TextStyle::PLAIN
+    }
+
+    /// 斜体在第 `row` 行的水平偏移，字体高度为 `h`
+    pub fn shear(&self, row: usize, h: usize) -> usize {
+        if self.italic { (h - 1 - row) / 4 } else { 0 }
+    }
+}
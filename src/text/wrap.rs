@@ -0,0 +1,274 @@
+use super::{HAlign, Rect, measure_line};
+use crate::font::FontFace;
+use crate::print_string;
+use std::ops::Range;
+
+// 中日韩文字，可以在任意两个字之间换行
+fn is_cjk(ch: char) -> bool {
+    matches!(ch as u32,
+        0x1100..=0x11FF     // 谚文字母
+        | 0x2E80..=0x303F   // 部首、标点
+        | 0x3040..=0x30FF   // 平假名、片假名
+        | 0x3130..=0x318F
+        | 0x3400..=0x4DBF
+        | 0x4E00..=0x9FFF
+        | 0xAC00..=0xD7AF   // 谚文音节
+        | 0xF900..=0xFAFF
+        | 0xFF00..=0xFFEF   // 全角字符
+        | 0x20000..=0x2FA1F)
+}
+
+// 不能出现在行首的标点（避头尾）
+fn no_break_before(ch: char) -> bool {
+    matches!(
+        ch,
+        '，' | '。'
+            | '、'
+            | '；'
+            | '：'
+            | '？'
+            | '！'
+            | '）'
+            | '》'
+            | '」'
+            | '』'
+            | '】'
+            | '…'
+            | ','
+            | '.'
+            | ';'
+            | ':'
+            | '?'
+            | '!'
+            | ')'
+    )
+}
+
+/// 按宽度把文字分成若干可见行，返回每行在 `text` 中的字节范围。
+///
+/// 优先在空格、连字符以及中日韩文字之间断行，单词比一行还长时才在单词中间断开；
+/// `'\n'` 强制换行，换行处的空格不计入行内。
+pub fn wrap_text(font: &dyn FontFace, text: &str, width: u16) -> Vec<Range<usize>> {
+    let mut lines = Vec::new();
+    let mut para_start = 0;
+    for para in text.split('\n') {
+        let para_end = para_start + para.len();
+        let mut start = para_start;
+        loop {
+            let rest = &text[start..para_end];
+            let mut end = start;
+            let mut last_break = None;
+            let mut prev: Option<char> = None;
+            let mut overflow = false;
+            for (i, ch) in rest.char_indices() {
+                let pos = start + i;
+                if let Some(p) = prev
+                    && (is_cjk(ch) || is_cjk(p))
+                    && !no_break_before(ch)
+                {
+                    last_break = Some(pos);
+                }
+                let next = pos + ch.len_utf8();
+                if measure_line(font, &text[start..next]) > width && ch != ' ' {
+                    overflow = true;
+                    break;
+                }
+                end = next;
+                if ch == ' ' || ch == '-' {
+                    last_break = Some(next);
+                }
+                prev = Some(ch);
+            }
+            if !overflow {
+                lines.push(start..end);
+                break;
+            }
+            let cut = match last_break {
+                Some(b) if b > start => b,
+                // 一个字符都放不下时至少放一个，避免死循环
+                _ if end == start => start + rest.chars().next().map_or(0, char::len_utf8),
+                _ => end,
+            };
+            lines.push(start..start + text[start..cut].trim_end_matches(' ').len());
+            start =
+                cut + text[cut..para_end].len() - text[cut..para_end].trim_start_matches(' ').len();
+            if start >= para_end {
+                break;
+            }
+        }
+        para_start = para_end + 1;
+    }
+    lines
+}
+
+/// 文本框排版结果
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct TextLayout {
+    /// 实际显示的每一行（截断时包含省略号）
+    pub lines: Vec<String>,
+    /// 已显示的字符数，下一页从这里开始
+    pub consumed: usize,
+    /// 与 `consumed` 对应的字节偏移，可以直接用于切片
+    pub consumed_bytes: usize,
+    /// 文字是否没有全部放下
+    pub truncated: bool,
+}
+
+/// 文本框：在矩形内按词换行，超出底部的部分不绘制，可选用省略号截断
+pub struct TextBox<'a> {
+    rect: Rect,
+    font: &'a dyn FontFace,
+    align: HAlign,
+    ellipsis: bool,
+}
+
+impl<'a> TextBox<'a> {
+    pub fn new(rect: Rect, font: &'a dyn FontFace) -> Self {
+        TextBox {
+            rect,
+            font,
+            align: HAlign::Left,
+            ellipsis: false,
+        }
+    }
+
+    pub fn align(mut self, align: HAlign) -> Self {
+        self.align = align;
+        self
+    }
+
+    /// 放不下时在最后一行末尾显示省略号
+    pub fn ellipsis(mut self, ellipsis: bool) -> Self {
+        self.ellipsis = ellipsis;
+        self
+    }
+
+    /// 只排版不绘制
+    pub fn layout(&self, text: &str) -> TextLayout {
+        let line_h = self.font.get_font_height().max(1);
+        let max_lines = (self.rect.h / line_h) as usize;
+        let wrapped = wrap_text(self.font, text, self.rect.w as u16);
+        let truncated = wrapped.len() > max_lines;
+
+        let mut lines: Vec<String> = wrapped
+            .iter()
+            .take(max_lines)
+            .map(|r| text[r.clone()].to_string())
+            .collect();
+        let mut consumed_bytes = wrapped[..max_lines.min(wrapped.len())]
+            .last()
+            .map_or(0, |r| r.end);
+
+        if truncated && self.ellipsis && max_lines > 0 {
+            // 字体有 '…' 就用它，否则用三个点
+            let mark = if self.font.get_metrics('…').is_some() {
+                "…"
+            } else {
+                "..."
+            };
+            let start = wrapped[max_lines - 1].start;
+            let last = &mut lines[max_lines - 1];
+            while !last.is_empty()
+                && measure_line(self.font, &format!("{}{}", last, mark)) > self.rect.w as u16
+            {
+                last.pop();
+            }
+            consumed_bytes = start + last.len();
+            last.push_str(mark);
+        } else if truncated {
+            // 跳过分页处的空白和换行，下一页从可见字符开始
+            let rest = &text[consumed_bytes..];
+            consumed_bytes += rest.len() - rest.trim_start_matches([' ', '\n']).len();
+        } else {
+            consumed_bytes = text.len();
+        }
+
+        TextLayout {
+            lines,
+            consumed: text[..consumed_bytes].chars().count(),
+            consumed_bytes,
+            truncated,
+        }
+    }
+
+    /// 排版并绘制到帧缓冲
+    pub fn draw(&self, text: &str) -> TextLayout {
+        let layout = self.layout(text);
+        let mut y = self.rect.y;
+        for line in &layout.lines {
+            let x = self.align.place(self.rect, measure_line(self.font, line));
+            let _ = print_string(x, y, self.font, line);
+            y = y.saturating_add(self.font.get_font_height());
+        }
+        layout
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::font::{FONT8X8, Font};
+
+    // 每个字 6 列宽的中文测试字体
+    const CJK: Font = FONT8X8.with_glyphs(6, 8, &['中', '文', '，'], &[0xff; 18]);
+
+    fn wrapped<'a>(font: &dyn FontFace, text: &'a str, width: u16) -> Vec<&'a str> {
+        wrap_text(font, text, width)
+            .into_iter()
+            .map(|r| &text[r])
+            .collect()
+    }
+
+    #[test]
+    fn breaks_at_spaces_and_hyphens() {
+        assert_eq!(wrapped(&FONT8X8, "ab cd ef", 30), ["ab cd", "ef"]);
+        assert_eq!(wrapped(&FONT8X8, "abc de", 24), ["abc", "de"]);
+        assert_eq!(wrapped(&FONT8X8, "ab-cdef", 30), ["ab-", "cdef"]);
+        assert_eq!(wrapped(&FONT8X8, "a\n\nb", 30), ["a", "", "b"]);
+    }
+
+    #[test]
+    fn overlong_words_break_anywhere() {
+        assert_eq!(wrapped(&FONT8X8, "abcdefgh", 18), ["abc", "def", "gh"]);
+        // 一个字符都放不下时每行放一个
+        assert_eq!(wrapped(&FONT8X8, "ab", 3), ["a", "b"]);
+    }
+
+    #[test]
+    fn cjk_breaks_between_characters() {
+        assert_eq!(wrapped(&CJK, "中文中文", 18), ["中文中", "文"]);
+        assert_eq!(wrapped(&CJK, "ab中文", 18), ["ab中", "文"]);
+        // 逗号不能出现在行首，和前一个字一起换到下一行
+        assert_eq!(wrapped(&CJK, "中文，", 12), ["中", "文，"]);
+    }
+
+    #[test]
+    fn ellipsis_replaces_the_cut_text() {
+        let layout = TextBox::new(Rect::new(0, 0, 30, 8), &FONT8X8)
+            .ellipsis(true)
+            .layout("hello world");
+        assert_eq!(layout.lines, ["he..."]);
+        assert!(layout.truncated);
+        assert_eq!((layout.consumed, layout.consumed_bytes), (2, 2));
+    }
+
+    #[test]
+    fn consumed_continues_on_the_next_page() {
+        let text = "ab cd ef gh ij";
+        let layout = TextBox::new(Rect::new(0, 0, 30, 16), &FONT8X8).layout(text);
+        assert_eq!(layout.lines, ["ab cd", "ef gh"]);
+        assert_eq!(&text[layout.consumed_bytes..], "ij");
+
+        // 多字节文字的字符数和字节数不同
+        let text = "中文中文中";
+        let page = TextBox::new(Rect::new(0, 0, 12, 8), &CJK);
+        let layout = page.layout(text);
+        assert_eq!(layout.lines, ["中文"]);
+        assert_eq!((layout.consumed, layout.consumed_bytes), (2, 6));
+        let rest = &text[layout.consumed_bytes..];
+        assert_eq!(page.layout(rest).lines, ["中文"]);
+        let last = page.layout("中");
+        assert!(!last.truncated);
+        assert_eq!((last.consumed, last.consumed_bytes), (1, 3));
+    }
+}
@@ -0,0 +1,311 @@
+use crate::font::{Icon, SharedFont};
+use crate::text::TextBox;
+use crate::{Buffer, Display, HEIGHT, Rect, WIDTH};
+use std::fmt::{self, Write};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex, Once};
+use std::thread::{self, ThreadId};
+use std::time::Duration;
+use tracing_core::field::{Field, Visit};
+use tracing_core::span::{Attributes, Id, Record};
+use tracing_core::{Event, Level, Subscriber};
+use tracing_subscriber::layer::{Context, Layer};
+use tracing_subscriber::registry::LookupSpan;
+
+// 把字段格式化为 `消息 名称=值`
+#[derive(Default)]
+struct Fields(String);
+
+impl Fields {
+    fn separate(&mut self) {
+        if !self.0.is_empty() {
+            self.0.push(' ');
+        }
+    }
+}
+
+impl Visit for Fields {
+    fn record_str(&mut self, field: &Field, value: &str) {
+        self.separate();
+        if field.name() == "message" {
+            self.0.push_str(value);
+        } else {
+            let _ = write!(self.0, "{}={}", field.name(), value);
+        }
+    }
+
+    fn record_debug(&mut self, field: &Field, value: &dyn fmt::Debug) {
+        self.separate();
+        if field.name() == "message" {
+            let _ = write!(self.0, "{:?}", value);
+        } else {
+            let _ = write!(self.0, "{}={:?}", field.name(), value);
+        }
+    }
+}
+
+// 保存在 span 扩展中的显示文字
+struct SpanLabel(String);
+
+struct State {
+    display: Display,
+    buffer: Buffer,
+    font: SharedFont,
+    active: Vec<(ThreadId, Id, String)>, // 各线程已进入的 span，最后进入的显示在状态栏
+}
+
+impl State {
+    // 第一行是状态栏，下面是最近的事件
+    fn render(&mut self) {
+        let font = self.font.as_ref();
+        let h = font.get_font_height();
+        self.display.newframe();
+        if let Some((_, _, label)) = self.active.last() {
+            TextBox::new(Rect::new(0, 0, WIDTH as u8, h), font)
+                .ellipsis(true)
+                .draw(label);
+        }
+        for x in 0..WIDTH as u8 {
+            crate::setpixel(x, h, true);
+        }
+        crate::draw_buffer(&self.buffer, 0, h + 2, font);
+        self.display.showframe();
+    }
+}
+
+/// `tracing` 的显示层：状态栏显示当前进入的 span（如 "updating firmware"），
+/// 下面按时间顺序显示最近的事件。
+///
+/// 事件和 span 变化只更新缓冲区，由后台线程按 `refresh_interval` 刷新屏幕
+pub struct OledLayer {
+    state: Arc<Mutex<State>>,
+    level: Level,
+    interval: Duration,
+    dirty: Arc<AtomicBool>,
+    started: Once,
+}
+
+impl OledLayer {
+    /// 默认显示 `INFO` 及以上级别的事件，每500毫秒最多刷新一次
+    pub fn new(display: Display, font: SharedFont) -> Self {
+        let h = font.get_font_height().max(1) as usize;
+        let rows = HEIGHT.saturating_sub(h + 2) / h;
+        let buffer = Buffer::terminal(rows).wrap(font.clone(), WIDTH as u16);
+        OledLayer {
+            state: Arc::new(Mutex::new(State {
+                display,
+                buffer,
+                font,
+                active: Vec::new(),
+            })),
+            level: Level::INFO,
+            interval: Duration::from_millis(500),
+            dirty: Arc::new(AtomicBool::new(false)),
+            started: Once::new(),
+        }
+    }
+
+    /// 显示的最详细的事件级别
+    pub fn level(mut self, level: Level) -> Self {
+        self.level = level;
+        self
+    }
+
+    /// 两次刷新屏幕的最短间隔
+    pub fn refresh_interval(mut self, interval: Duration) -> Self {
+        self.interval = interval;
+        self
+    }
+
+    /// 立即刷新屏幕
+    pub fn refresh(&self) {
+        if let Ok(mut state) = self.state.lock() {
+            state.render();
+            self.dirty.store(false, Ordering::Release);
+        }
+    }
+
+    // 标记需要刷新，第一次调用时启动刷新线程
+    fn touch(&self) {
+        self.dirty.store(true, Ordering::Release);
+        self.started.call_once(|| {
+            let state = self.state.clone();
+            let dirty = self.dirty.clone();
+            let interval = self.interval;
+            thread::spawn(move || {
+                loop {
+                    thread::sleep(interval);
+                    if dirty.swap(false, Ordering::AcqRel)
+                        && let Ok(mut state) = state.lock()
+                    {
+                        state.render();
+                    }
+                }
+            });
+        });
+    }
+}
+
+// 级别图标，字体不支持图标时用字母代替
+fn indicator(font: &SharedFont, level: &Level) -> char {
+    let icon = match *level {
+        Level::ERROR => Icon::Cross,
+        Level::WARN => Icon::Warning,
+        Level::INFO => Icon::Info,
+        Level::DEBUG => Icon::ArrowRight,
+        _ => Icon::ArrowDown,
+    };
+    if font.get_metrics(icon.char()).is_some() {
+        icon.char()
+    } else {
+        level.as_str().chars().next().unwrap_or('?')
+    }
+}
+
+impl<S> Layer<S> for OledLayer
+where
+    S: Subscriber + for<'a> LookupSpan<'a>,
+{
+    fn on_new_span(&self, attrs: &Attributes<'_>, id: &Id, ctx: Context<'_, S>) {
+        let mut fields = Fields(attrs.metadata().name().to_string());
+        attrs.record(&mut fields);
+        if let Some(span) = ctx.span(id) {
+            span.extensions_mut().insert(SpanLabel(fields.0));
+        }
+    }
+
+    fn on_record(&self, id: &Id, values: &Record<'_>, ctx: Context<'_, S>) {
+        let Some(span) = ctx.span(id) else {
+            return;
+        };
+        let mut extensions = span.extensions_mut();
+        let Some(label) = extensions.get_mut::<SpanLabel>() else {
+            return;
+        };
+        let mut fields = Fields(std::mem::take(&mut label.0));
+        values.record(&mut fields);
+        label.0 = fields.0;
+        // 已进入的 span 同时更新状态栏
+        if let Ok(mut state) = self.state.lock() {
+            for (_, active, text) in state.active.iter_mut() {
+                if active == id {
+                    text.clone_from(&label.0);
+                }
+            }
+        }
+        self.touch();
+    }
+
+    fn on_enter(&self, id: &Id, ctx: Context<'_, S>) {
+        let label = ctx
+            .span(id)
+            .and_then(|span| span.extensions().get::<SpanLabel>().map(|l| l.0.clone()));
+        if let (Some(label), Ok(mut state)) = (label, self.state.lock()) {
+            state
+                .active
+                .push((thread::current().id(), id.clone(), label));
+            self.touch();
+        }
+    }
+
+    fn on_exit(&self, id: &Id, _ctx: Context<'_, S>) {
+        // 同一个 span 可以在多个线程中进入，只移除当前线程的那一项
+        let thread = thread::current().id();
+        if let Ok(mut state) = self.state.lock()
+            && let Some(i) = state
+                .active
+                .iter()
+                .rposition(|(t, active, _)| *t == thread && active == id)
+        {
+            state.active.remove(i);
+            self.touch();
+        }
+    }
+
+    fn on_close(&self, id: Id, _ctx: Context<'_, S>) {
+        if let Ok(mut state) = self.state.lock() {
+            state.active.retain(|(_, active, _)| *active != id);
+        }
+    }
+
+    fn on_event(&self, event: &Event<'_>, _ctx: Context<'_, S>) {
+        let level = event.metadata().level();
+        if *level > self.level {
+            return;
+        }
+        let Ok(mut state) = self.state.lock() else {
+            return;
+        };
+        let mut fields = Fields::default();
+        event.record(&mut fields);
+        let line = format!("{} {}\n", indicator(&state.font, level), fields.0);
+        state.buffer.push(line);
+        drop(state);
+        self.touch();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::font::FONT8X8;
+    use std::sync::mpsc;
+    use tracing_subscriber::prelude::*;
+
+    fn labels(state: &Mutex<State>) -> Vec<(ThreadId, String)> {
+        let state = state.lock().unwrap();
+        state
+            .active
+            .iter()
+            .map(|(t, _, l)| (*t, l.clone()))
+            .collect()
+    }
+
+    #[test]
+    fn spans_are_tracked_per_thread() {
+        // 不让刷新线程在测试期间改写共用的帧缓冲
+        let layer = OledLayer::new(Display::simulated(), Arc::new(FONT8X8))
+            .refresh_interval(Duration::from_secs(3600));
+        let state = layer.state.clone();
+        let dispatch = ::tracing::Dispatch::new(tracing_subscriber::registry().with(layer));
+        let main = thread::current().id();
+
+        ::tracing::dispatcher::with_default(&dispatch, || {
+            let job = ::tracing::info_span!("job", n = 1);
+            let outer = job.clone().entered();
+
+            let (entered, wait) = (mpsc::channel(), mpsc::channel::<()>());
+            let worker = {
+                let (dispatch, job) = (dispatch.clone(), job.clone());
+                thread::spawn(move || {
+                    ::tracing::dispatcher::with_default(&dispatch, || {
+                        let _inner = job.entered();
+                        let step = ::tracing::info_span!("step").entered();
+                        entered.0.send(thread::current().id()).unwrap();
+                        wait.1.recv().unwrap();
+                        drop(step);
+                    });
+                })
+            };
+            let other = entered.1.recv().unwrap();
+            assert_eq!(
+                labels(&state),
+                [
+                    (main, "job n=1".to_string()),
+                    (other, "job n=1".to_string()),
+                    (other, "step".to_string()),
+                ]
+            );
+
+            // 主线程退出 job 不影响另一个线程进入的同一个 span
+            drop(outer);
+            assert_eq!(
+                labels(&state),
+                [(other, "job n=1".to_string()), (other, "step".to_string())]
+            );
+            wait.0.send(()).unwrap();
+            worker.join().unwrap();
+            assert!(labels(&state).is_empty());
+        });
+    }
+}